@@ -0,0 +1,568 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use solana_account_decoder::parse_token::token_amount_to_ui_amount;
+use solana_account_decoder::{UiAccount, UiDataSliceConfig};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient, rpc_config::RpcProgramAccountsConfig,
+    rpc_filter::RpcFilterType, rpc_response::RpcTokenAccountBalance,
+};
+use solana_program::program_pack::Pack;
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    hash::Hash,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Signature,
+    transaction::Transaction,
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    path::Path,
+    str::FromStr,
+    sync::{Mutex, PoisonError},
+};
+
+/// Where account state is read from and transactions are sent to: a live RPC node or a local
+/// store of accounts.
+///
+/// A [`MemoryAccountSource`] records sent transactions without executing them, so commands
+/// that read back what they wrote, or send several dependent transactions, only behave like
+/// they would on a cluster against an RPC node or a local validator.
+#[async_trait]
+pub trait AccountSource: Send + Sync {
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>>;
+
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>>;
+
+    /// Applies the config's filters and data slice; the encoding and commitment are up to
+    /// the source.
+    async fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> Result<Vec<(Pubkey, Account)>>;
+
+    /// The largest token accounts of `mint`, largest first, as `getTokenLargestAccounts`
+    /// returns them.
+    async fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Vec<RpcTokenAccountBalance>>;
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64>;
+
+    async fn get_latest_blockhash(&self) -> Result<Hash>;
+
+    async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> Result<Signature>;
+}
+
+#[async_trait]
+impl AccountSource for RpcClient {
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        Ok(self
+            .get_account_with_commitment(pubkey, self.commitment())
+            .await?
+            .value)
+    }
+
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        Ok(RpcClient::get_multiple_accounts(self, pubkeys).await?)
+    }
+
+    async fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        Ok(RpcClient::get_program_accounts_with_config(self, program_id, config).await?)
+    }
+
+    async fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Vec<RpcTokenAccountBalance>> {
+        Ok(RpcClient::get_token_largest_accounts(self, mint).await?)
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        Ok(RpcClient::get_minimum_balance_for_rent_exemption(self, data_len).await?)
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(RpcClient::get_latest_blockhash(self).await?)
+    }
+
+    async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        Ok(RpcClient::send_and_confirm_transaction(self, transaction).await?)
+    }
+}
+
+/// Most accounts the RPC node returns for `getTokenLargestAccounts`.
+const MAX_LARGEST_ACCOUNTS: usize = 20;
+
+/// An in-memory account store, seeded from JSON fixtures or dumps, for running commands and
+/// tests without a network.
+///
+/// Accepts the output of `solana account <address> --output json`, arrays of the same
+/// `{ "pubkey", "account" }` objects as `getProgramAccounts` returns them, and the
+/// `{ "context", "value" }` result of `getProgramAccounts` with `withContext` set. Only the
+/// latter records the slot the accounts were read at; see [`MemoryAccountSource::check_slot`].
+/// `solana-ledger-tool` exports aren't supported.
+///
+/// Sent transactions are signature-checked and recorded but not executed, so the account
+/// state never changes.
+#[derive(Debug, Default)]
+pub struct MemoryAccountSource {
+    accounts: BTreeMap<Pubkey, Account>,
+    /// Slot of each loaded dump, `None` for dumps that don't record one.
+    dump_slots: BTreeSet<Option<u64>>,
+    blockhash: Hash,
+    sent_transactions: Mutex<Vec<Transaction>>,
+}
+
+#[derive(Deserialize)]
+struct KeyedAccount {
+    pubkey: String,
+    account: UiAccount,
+}
+
+#[derive(Deserialize)]
+struct DumpContext {
+    slot: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AccountDump {
+    One(KeyedAccount),
+    Many(Vec<KeyedAccount>),
+    WithContext {
+        context: DumpContext,
+        value: Vec<KeyedAccount>,
+    },
+}
+
+impl MemoryAccountSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads every `.json` file in `dir`.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let mut source = Self::new();
+
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("json") {
+                source.load_file(&path)?;
+            }
+        }
+
+        Ok(source)
+    }
+
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let dump: AccountDump = serde_json::from_reader(File::open(path)?)
+            .map_err(|e| anyhow!("{} is not an account dump: {}", path.display(), e))?;
+
+        self.load_dump(dump)
+            .map_err(|e| anyhow!("Failed to load {}: {}", path.display(), e))
+    }
+
+    pub fn load_json(&mut self, json: &str) -> Result<()> {
+        self.load_dump(serde_json::from_str(json)?)
+    }
+
+    fn load_dump(&mut self, dump: AccountDump) -> Result<()> {
+        let (keyed_accounts, slot) = match dump {
+            AccountDump::One(keyed_account) => (vec![keyed_account], None),
+            AccountDump::Many(keyed_accounts) => (keyed_accounts, None),
+            AccountDump::WithContext { context, value } => (value, Some(context.slot)),
+        };
+        self.dump_slots.insert(slot);
+
+        for keyed_account in keyed_accounts {
+            let pubkey = Pubkey::from_str(&keyed_account.pubkey)?;
+            let account: Account = keyed_account
+                .account
+                .decode()
+                .ok_or_else(|| anyhow!("Account {} is not base58 or base64 encoded", pubkey))?;
+            self.accounts.insert(pubkey, account);
+        }
+
+        Ok(())
+    }
+
+    pub fn insert(&mut self, pubkey: Pubkey, account: Account) {
+        self.accounts.insert(pubkey, account);
+    }
+
+    /// The slot the loaded dumps were read at, if they all record the same one.
+    pub fn slot(&self) -> Option<u64> {
+        match self.dump_slots.iter().collect::<Vec<_>>()[..] {
+            [slot] => *slot,
+            _ => None,
+        }
+    }
+
+    /// Fails unless every loaded dump records that it was read at `slot`, so a snapshot taken
+    /// "at a slot" can't silently mix in state from other slots.
+    pub fn check_slot(&self, slot: u64) -> Result<()> {
+        if self.dump_slots.is_empty() {
+            return Err(anyhow!("No account dumps are loaded"));
+        }
+        if self.dump_slots.contains(&None) {
+            return Err(anyhow!(
+                "Some account dumps don't record their slot; dump them with getProgramAccounts \
+                 and withContext set"
+            ));
+        }
+
+        let other_slots: Vec<String> = self
+            .dump_slots
+            .iter()
+            .flatten()
+            .filter(|dump_slot| **dump_slot != slot)
+            .map(|dump_slot| dump_slot.to_string())
+            .collect();
+        if !other_slots.is_empty() {
+            return Err(anyhow!(
+                "Account dumps were read at slots {} rather than {}",
+                other_slots.join(", "),
+                slot
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Sets the blockhash returned by `get_latest_blockhash`.
+    pub fn set_blockhash(&mut self, blockhash: Hash) {
+        self.blockhash = blockhash;
+    }
+
+    /// Transactions passed to `send_and_confirm_transaction`, in the order they were sent.
+    pub fn sent_transactions(&self) -> Vec<Transaction> {
+        self.sent_transactions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+}
+
+#[async_trait]
+impl AccountSource for MemoryAccountSource {
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        Ok(self.accounts.get(pubkey).cloned())
+    }
+
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        Ok(pubkeys
+            .iter()
+            .map(|pubkey| self.accounts.get(pubkey).cloned())
+            .collect())
+    }
+
+    async fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        let filters = config.filters.unwrap_or_default();
+        let data_slice = config.account_config.data_slice;
+
+        Ok(self
+            .accounts
+            .iter()
+            .filter(|(_, account)| account.owner == *program_id)
+            .filter(|(_, account)| matches_filters(account, &filters))
+            .map(|(pubkey, account)| (*pubkey, slice_account(account, data_slice)))
+            .collect())
+    }
+
+    async fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Vec<RpcTokenAccountBalance>> {
+        let decimals = self
+            .accounts
+            .get(mint)
+            .and_then(|account| Mint::unpack(&account.data).ok())
+            .map(|mint| mint.decimals)
+            .ok_or_else(|| anyhow!("Invalid param: could not find mint {}", mint))?;
+
+        let mut balances: Vec<(Pubkey, u64)> = self
+            .accounts
+            .iter()
+            .filter(|(_, account)| account.owner == spl_token::ID)
+            .filter_map(|(pubkey, account)| {
+                let token = TokenAccount::unpack(&account.data).ok()?;
+                (token.mint == *mint).then_some((*pubkey, token.amount))
+            })
+            .collect();
+        balances.sort_by_key(|(_, amount)| std::cmp::Reverse(*amount));
+        balances.truncate(MAX_LARGEST_ACCOUNTS);
+
+        Ok(balances
+            .into_iter()
+            .map(|(address, amount)| RpcTokenAccountBalance {
+                address: address.to_string(),
+                amount: token_amount_to_ui_amount(amount, decimals),
+            })
+            .collect())
+    }
+
+    /// Uses the default rent parameters, which mainnet, devnet and testnet all run with.
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        Ok(Rent::default().minimum_balance(data_len))
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(self.blockhash)
+    }
+
+    async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        transaction.verify()?;
+
+        self.sent_transactions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(transaction.clone());
+
+        Ok(transaction.signatures[0])
+    }
+}
+
+pub fn matches_filters(account: &Account, filters: &[RpcFilterType]) -> bool {
+    let account = AccountSharedData::from(account.clone());
+    filters.iter().all(|filter| filter.allows(&account))
+}
+
+fn slice_account(account: &Account, data_slice: Option<UiDataSliceConfig>) -> Account {
+    let mut account = account.clone();
+
+    if let Some(UiDataSliceConfig { offset, length }) = data_slice {
+        let start = offset.min(account.data.len());
+        let end = offset.saturating_add(length).min(account.data.len());
+        account.data = account.data[start..end].to_vec();
+    }
+
+    account
+}
+
+/// Fixture accounts under `tests/fixtures/accounts`, shared by the offline unit tests.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::*;
+    use solana_sdk::signature::{keypair_from_seed, Keypair};
+
+    /// A padded metadata account of mint `[1; 32]`, updatable by `update_authority`, with
+    /// verified creator `[3; 32]` and verified collection `[5; 32]`.
+    pub const METADATA: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/accounts/metadata.json"
+    ));
+
+    pub const MINT: Pubkey = Pubkey::new_from_array([1; 32]);
+    pub const CREATOR: Pubkey = Pubkey::new_from_array([3; 32]);
+    pub const COLLECTION_MINT: Pubkey = Pubkey::new_from_array([5; 32]);
+
+    pub fn update_authority() -> Keypair {
+        keypair_from_seed(&[7; 32]).unwrap()
+    }
+
+    pub fn fixture_source() -> MemoryAccountSource {
+        let mut source = MemoryAccountSource::new();
+        source.load_json(METADATA).unwrap();
+        source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes};
+
+    #[test]
+    fn test_dump_source_program_accounts() {
+        let program_id = Pubkey::new_unique();
+        let dump = format!(
+            r#"[
+                {{ "pubkey": "{}", "account": {{ "lamports": 1, "data": ["AQIDBA==", "base64"],
+                   "owner": "{}", "executable": false, "rentEpoch": 0 }} }},
+                {{ "pubkey": "{}", "account": {{ "lamports": 1, "data": ["BQYHCA==", "base64"],
+                   "owner": "{}", "executable": false, "rentEpoch": 0 }} }}
+            ]"#,
+            Pubkey::new_unique(),
+            program_id,
+            Pubkey::new_unique(),
+            program_id
+        );
+        let path = std::env::temp_dir().join(format!("{}.json", Pubkey::new_unique()));
+        std::fs::write(&path, dump).unwrap();
+
+        let mut source = MemoryAccountSource::new();
+        source.load_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(source.len(), 2);
+
+        #[allow(deprecated)]
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(4),
+                RpcFilterType::Memcmp(Memcmp {
+                    offset: 1,
+                    bytes: MemcmpEncodedBytes::Bytes(vec![2, 3]),
+                    encoding: None,
+                }),
+            ]),
+            account_config: solana_client::rpc_config::RpcAccountInfoConfig {
+                data_slice: Some(UiDataSliceConfig {
+                    offset: 2,
+                    length: 8,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let accounts = futures::executor::block_on(
+            source.get_program_accounts_with_config(&program_id, config),
+        )
+        .unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].1.data, vec![3, 4]);
+    }
+
+    #[test]
+    fn test_dump_slot() {
+        let dump = |slot: u64| {
+            format!(
+                r#"{{ "context": {{ "slot": {}, "apiVersion": "1.16.27" }}, "value": [
+                    {{ "pubkey": "{}", "account": {{ "lamports": 1, "data": ["", "base64"],
+                       "owner": "{}", "executable": false, "rentEpoch": 0 }} }}
+                ] }}"#,
+                slot,
+                Pubkey::new_unique(),
+                Pubkey::new_unique()
+            )
+        };
+
+        let mut source = MemoryAccountSource::new();
+        source.load_json(&dump(100)).unwrap();
+        source.load_json(&dump(100)).unwrap();
+        assert_eq!(source.len(), 2);
+        assert_eq!(source.slot(), Some(100));
+        assert!(source.check_slot(100).is_ok());
+        assert!(source.check_slot(101).is_err());
+
+        source.load_json(&dump(101)).unwrap();
+        assert_eq!(source.slot(), None);
+        assert!(source.check_slot(100).is_err());
+
+        let mut source = fixtures::fixture_source();
+        source.load_json(&dump(100)).unwrap();
+        assert!(source.check_slot(100).is_err());
+    }
+
+    #[test]
+    fn test_token_largest_accounts() {
+        let mint = Pubkey::new_unique();
+        let token_account = |amount: u64| {
+            let mut data = vec![0; TokenAccount::LEN];
+            TokenAccount::pack(
+                TokenAccount {
+                    mint,
+                    owner: Pubkey::new_unique(),
+                    amount,
+                    state: spl_token::state::AccountState::Initialized,
+                    ..Default::default()
+                },
+                &mut data,
+            )
+            .unwrap();
+            Account {
+                lamports: 1,
+                data,
+                owner: spl_token::ID,
+                executable: false,
+                rent_epoch: 0,
+            }
+        };
+        let mut mint_data = vec![0; Mint::LEN];
+        Mint::pack(
+            Mint {
+                decimals: 2,
+                is_initialized: true,
+                ..Default::default()
+            },
+            &mut mint_data,
+        )
+        .unwrap();
+
+        let mut source = MemoryAccountSource::new();
+        let largest = Pubkey::new_unique();
+        source.insert(largest, token_account(500));
+        source.insert(Pubkey::new_unique(), token_account(3));
+        source.insert(Pubkey::new_unique(), token_account(0));
+        assert!(futures::executor::block_on(source.get_token_largest_accounts(&mint)).is_err());
+
+        source.insert(
+            mint,
+            Account {
+                lamports: 1,
+                data: mint_data,
+                owner: spl_token::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        let balances =
+            futures::executor::block_on(source.get_token_largest_accounts(&mint)).unwrap();
+        assert_eq!(balances.len(), 3);
+        assert_eq!(balances[0].address, largest.to_string());
+        assert_eq!(balances[0].amount.ui_amount_string, "5");
+        assert_eq!(balances[2].amount.amount, "0");
+    }
+
+    #[test]
+    fn test_memory_source_records_transactions() {
+        use solana_sdk::{signer::Signer, system_instruction};
+
+        let mut source = fixtures::fixture_source();
+        let blockhash = Hash::new_unique();
+        source.set_blockhash(blockhash);
+
+        let payer = fixtures::update_authority();
+        let ix = system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+        let recent_blockhash = futures::executor::block_on(source.get_latest_blockhash()).unwrap();
+        assert_eq!(recent_blockhash, blockhash);
+
+        let tx =
+            Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], blockhash);
+        let signature =
+            futures::executor::block_on(source.send_and_confirm_transaction(&tx)).unwrap();
+        assert_eq!(signature, tx.signatures[0]);
+        assert_eq!(source.sent_transactions(), vec![tx.clone()]);
+
+        let unsigned = Transaction {
+            signatures: vec![Signature::default()],
+            ..tx
+        };
+        assert!(
+            futures::executor::block_on(source.send_and_confirm_transaction(&unsigned)).is_err()
+        );
+    }
+}
@@ -1,11 +1,9 @@
-use anyhow::Result as AnyResult;
-use borsh::BorshDeserialize;
+use anyhow::{anyhow, Result as AnyResult};
 use mpl_token_metadata::{
     id,
     instruction::{burn_edition_nft, burn_nft},
-    state::{Edition, Metadata, TokenMetadataAccount},
+    state::{Edition, TokenMetadataAccount},
 };
-pub use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
     pubkey::Pubkey,
     signer::{keypair::Keypair, Signer},
@@ -16,12 +14,14 @@ use spl_token;
 use std::sync::Arc;
 
 use crate::{
+    account_source::AccountSource,
+    commands::decode::decode,
     derive::{derive_edition_marker_pda, derive_edition_pda, derive_metadata_pda},
     utils::get_largest_token_account_owner,
 };
 
 pub struct BurnArgs<'a> {
-    pub client: &'a RpcClient,
+    pub client: &'a dyn AccountSource,
     pub keypair: Arc<Keypair>,
     pub mint_pubkey: Pubkey,
 }
@@ -32,8 +32,7 @@ pub async fn burn<'a>(args: &BurnArgs<'a>) -> AnyResult<Transaction> {
     let metadata_pubkey = derive_metadata_pda(&args.mint_pubkey);
     let master_edition = derive_edition_pda(&args.mint_pubkey);
 
-    let md_account = args.client.get_account_data(&metadata_pubkey).await?;
-    let metadata = Metadata::deserialize(&mut md_account.as_slice())?;
+    let metadata = decode(args.client, &args.mint_pubkey).await?;
 
     // Is it a verified collection item?
     let collection_md = if let Some(collection) = metadata.collection {
@@ -72,7 +71,7 @@ pub async fn burn<'a>(args: &BurnArgs<'a>) -> AnyResult<Transaction> {
 }
 
 pub struct BurnPrintArgs<'a> {
-    pub client: &'a RpcClient,
+    pub client: &'a dyn AccountSource,
     pub keypair: Arc<Keypair>,
     pub mint_pubkey: Pubkey,
     pub master_mint_pubkey: Pubkey,
@@ -84,7 +83,7 @@ pub async fn burn_print<'a>(args: BurnPrintArgs<'a>) -> AnyResult<Transaction> {
 
     // Find the master edition holder.
     let master_edition_owner =
-        get_largest_token_account_owner(&args.client, args.master_mint_pubkey).await?;
+        get_largest_token_account_owner(args.client, args.master_mint_pubkey).await?;
     let master_edition_token =
         get_associated_token_address(&master_edition_owner, &args.master_mint_pubkey);
 
@@ -94,8 +93,12 @@ pub async fn burn_print<'a>(args: BurnPrintArgs<'a>) -> AnyResult<Transaction> {
     let master_edition_pda = derive_edition_pda(&args.master_mint_pubkey);
     let print_edition_pda = derive_edition_pda(&args.mint_pubkey);
 
-    let data = args.client.get_account_data(&print_edition_pda).await?;
-    let print_edition = Edition::safe_deserialize(data.as_slice())?;
+    let account = args
+        .client
+        .get_account(&print_edition_pda)
+        .await?
+        .ok_or_else(|| anyhow!("Print edition {} not found", print_edition_pda))?;
+    let print_edition = Edition::safe_deserialize(account.data.as_slice())?;
 
     let edition_marker_pda =
         derive_edition_marker_pda(&args.master_mint_pubkey, print_edition.edition);
@@ -1,9 +1,8 @@
+pub use crate::account_source::AccountSource;
 pub use anyhow::{anyhow, Result as AnyResult};
-pub use borsh::BorshDeserialize;
 pub use futures::future::select_all;
 pub use indexmap::IndexMap;
 pub use log::info;
-pub use mpl_token_metadata::state::Metadata;
 pub use mpl_token_metadata::{
     id as metadata_program_id,
     instruction::{
@@ -13,7 +12,6 @@ pub use mpl_token_metadata::{
     state::Collection as MdCollection,
 };
 pub use serde::{Deserialize, Serialize};
-pub use solana_client::nonblocking::rpc_client::RpcClient;
 pub use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
 pub use std::cmp;
 pub use std::collections::HashMap;
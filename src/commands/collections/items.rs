@@ -1,18 +1,21 @@
 use super::common::*;
 use super::data::*;
 use crate::commands::theindexio::THE_INDEX_MAINNET;
-use crate::derive::derive_metadata_pda;
-use borsh::BorshDeserialize;
+use crate::commands::decode::decode;
+use crate::output::{records_to_string, OutputFormat};
 
 pub async fn get_collection_items(
     collection_mint: String,
     method: GetCollectionItemsMethods,
     api_key: Option<String>,
+    format: OutputFormat,
 ) -> AnyResult<String> {
     match method {
         GetCollectionItemsMethods::TheIndexIO => {
             if let Some(key) = api_key {
-                return Ok(get_collection_items_by_the_index_io(collection_mint, key).await?);
+                return Ok(
+                    get_collection_items_by_the_index_io(collection_mint, key, format).await?,
+                );
             } else {
                 return Err(anyhow!(
                     "This method requires an index key for TheIndex.io."
@@ -25,6 +28,7 @@ pub async fn get_collection_items(
 pub async fn get_collection_items_by_the_index_io(
     collection_mint: String,
     api_key: String,
+    format: OutputFormat,
 ) -> AnyResult<String> {
     let jrpc = JRPCRequest::new("getNFTsByCollection", vec![collection_mint.clone()]);
     let url = format!("{THE_INDEX_MAINNET}/{api_key}");
@@ -42,11 +46,11 @@ pub async fn get_collection_items_by_the_index_io(
 
     mints.sort_unstable();
 
-    Ok(serde_json::to_string_pretty(&mints)?)
+    records_to_string(&mints, format)
 }
 
 pub async fn check_collection_items(
-    async_client: RpcClient,
+    client: Arc<dyn AccountSource>,
     collection_mint: String,
     mut mint_list: Vec<String>,
 ) -> AnyResult<()> {
@@ -56,12 +60,10 @@ pub async fn check_collection_items(
     let mut handles = Vec::new();
     let mut errors = Vec::new();
 
-    let client = Arc::new(async_client);
-
     for mint in mint_list.drain(0..cmp::min(mint_list.len(), PARALLEL_LIMIT)) {
         let client = client.clone();
         handles.push(tokio::spawn(async move {
-            get_mint_collection(&client, mint.to_string()).await
+            get_mint_collection(&*client, mint.to_string()).await
         }));
     }
 
@@ -105,7 +107,7 @@ pub async fn check_collection_items(
                 for mint in mint_list.drain(0..cmp::min(mint_list.len(), PARALLEL_LIMIT)) {
                     let client = client.clone();
                     handles.push(tokio::spawn(async move {
-                        get_mint_collection(&client, mint.to_string()).await
+                        get_mint_collection(&*client, mint.to_string()).await
                     }));
                 }
             }
@@ -128,13 +130,11 @@ pub async fn check_collection_items(
 }
 
 async fn get_mint_collection<'a>(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     mint: String,
 ) -> AnyResult<(String, Option<MdCollection>)> {
     let mint_pubkey = Pubkey::from_str(&mint)?;
-    let metadata_pubkey = derive_metadata_pda(&mint_pubkey);
-    let data = client.get_account_data(&metadata_pubkey).await?;
-    let md = <Metadata as BorshDeserialize>::deserialize(&mut data.as_slice())?;
+    let md = decode(client, &mint_pubkey).await?;
 
     Ok((mint, md.collection))
 }
@@ -1,11 +1,11 @@
 use super::common::*;
 use crate::parse::keypair::parse_keypair;
 use crate::{
+    commands::decode::decode,
     derive::{derive_collection_authority_record, derive_edition_pda, derive_metadata_pda},
     parse::solana_config::parse_solana_config,
     utils::send_and_confirm_transaction,
 };
-use borsh::BorshDeserialize;
 use mpl_token_metadata::instruction::{
     set_and_verify_sized_collection_item, set_collection_size, unverify_sized_collection_item,
     verify_sized_collection_item,
@@ -14,7 +14,7 @@ use mpl_token_metadata::instruction::{
 pub const OPEN_FILES_LIMIT: usize = 1024;
 
 pub async fn set_and_verify_nft_collection(
-    client: RpcClient,
+    client: &dyn AccountSource,
     keypair_path: Option<String>,
     nft_mint: String,
     collection_mint: String,
@@ -35,9 +35,7 @@ pub async fn set_and_verify_nft_collection(
     };
 
     // Is it a sized collection?
-    let collection_md_account = client.get_account_data(&collection_md_pubkey).await?;
-    let collection_metadata =
-        <Metadata as BorshDeserialize>::deserialize(&mut collection_md_account.as_slice())?;
+    let collection_metadata = decode(client, &collection_pubkey).await?;
 
     let set_and_verify_ix = if collection_metadata.collection_details.is_some() {
         set_and_verify_sized_collection_item(
@@ -65,13 +63,13 @@ pub async fn set_and_verify_nft_collection(
         )
     };
 
-    send_and_confirm_transaction(&client, keypair, &[set_and_verify_ix]).await?;
+    send_and_confirm_transaction(client, keypair, &[set_and_verify_ix]).await?;
 
     Ok(())
 }
 
 pub async fn unverify_nft_collection(
-    client: RpcClient,
+    client: &dyn AccountSource,
     keypair_path: Option<String>,
     nft_mint: String,
     collection_mint: String,
@@ -90,9 +88,7 @@ pub async fn unverify_nft_collection(
     };
 
     // Is it a sized collection?
-    let collection_md_account = client.get_account_data(&collection_md_pubkey).await?;
-    let collection_metadata =
-        <Metadata as BorshDeserialize>::deserialize(&mut collection_md_account.as_slice())?;
+    let collection_metadata = decode(client, &collection_pubkey).await?;
 
     // Choose which handler to use based on if collection is sized or not.
     let unverify_collection_ix = if collection_metadata.collection_details.is_some() {
@@ -118,13 +114,13 @@ pub async fn unverify_nft_collection(
         )
     };
 
-    send_and_confirm_transaction(&client, keypair, &[unverify_collection_ix]).await?;
+    send_and_confirm_transaction(client, keypair, &[unverify_collection_ix]).await?;
 
     Ok(())
 }
 
 pub async fn verify_nft_collection(
-    client: RpcClient,
+    client: &dyn AccountSource,
     keypair_path: Option<String>,
     nft_mint: String,
     collection_mint: String,
@@ -143,9 +139,7 @@ pub async fn verify_nft_collection(
     };
 
     // Is it a sized collection?
-    let collection_md_account = client.get_account_data(&collection_md_pubkey).await?;
-    let collection_metadata =
-        <Metadata as BorshDeserialize>::deserialize(&mut collection_md_account.as_slice())?;
+    let collection_metadata = decode(client, &collection_pubkey).await?;
 
     // Choose which handler to use based on if collection is sized or not.
     let verify_collection_ix = if collection_metadata.collection_details.is_some() {
@@ -172,13 +166,13 @@ pub async fn verify_nft_collection(
         )
     };
 
-    send_and_confirm_transaction(&client, keypair, &[verify_collection_ix]).await?;
+    send_and_confirm_transaction(client, keypair, &[verify_collection_ix]).await?;
 
     Ok(())
 }
 
 pub async fn approve_delegate(
-    client: RpcClient,
+    client: &dyn AccountSource,
     keypair_path: Option<String>,
     collection_mint: String,
     delegate_authority: String,
@@ -204,13 +198,13 @@ pub async fn approve_delegate(
         collection_pubkey,
     );
 
-    send_and_confirm_transaction(&client, keypair, &[approve_collection_auth_ix]).await?;
+    send_and_confirm_transaction(client, keypair, &[approve_collection_auth_ix]).await?;
 
     Ok(())
 }
 
 pub async fn revoke_delegate(
-    client: RpcClient,
+    client: &dyn AccountSource,
     keypair_path: Option<String>,
     collection_mint: String,
     delegate_authority: String,
@@ -235,13 +229,13 @@ pub async fn revoke_delegate(
         collection_pubkey,
     );
 
-    send_and_confirm_transaction(&client, keypair, &[revoke_collection_auth_ix]).await?;
+    send_and_confirm_transaction(client, keypair, &[revoke_collection_auth_ix]).await?;
 
     Ok(())
 }
 
 pub async fn set_size(
-    client: RpcClient,
+    client: &dyn AccountSource,
     keypair_path: Option<String>,
     collection_mint: String,
     size: u64,
@@ -261,7 +255,7 @@ pub async fn set_size(
         size,
     );
 
-    send_and_confirm_transaction(&client, keypair, &[set_collection_size_ix]).await?;
+    send_and_confirm_transaction(client, keypair, &[set_collection_size_ix]).await?;
 
     Ok(())
 }
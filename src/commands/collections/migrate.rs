@@ -1,17 +1,17 @@
 use super::common::*;
+use crate::commands::decode::decode;
 use crate::commands::snapshot::get_mint_accounts;
 use crate::{
     derive::{derive_collection_authority_record, derive_edition_pda, derive_metadata_pda},
     errors::MigrateError,
     utils::async_send_and_confirm_transaction,
 };
-use borsh::BorshDeserialize;
 use mpl_token_metadata::instruction::set_and_verify_sized_collection_item;
 use std::ops::{Deref, DerefMut};
 use tokio::sync::Semaphore;
 
 pub struct MigrateArgs<'a> {
-    pub client: &'a RpcClient,
+    pub client: &'a dyn AccountSource,
     pub keypair: Keypair,
     pub mint_address: String,
     pub candy_machine_id: Option<String>,
@@ -81,7 +81,7 @@ pub struct CacheItem {
 }
 
 async fn set_and_verify(
-    async_client: &RpcClient,
+    async_client: &dyn AccountSource,
     authority_keypair: Arc<Keypair>,
     nft_mint: String,
     collection_mint: String,
@@ -105,13 +105,9 @@ async fn set_and_verify(
     };
 
     // Is it a sized collection?
-    let collection_md_account = async_client
-        .get_account_data(&collection_md_pubkey)
+    let collection_metadata = decode(async_client, &collection_mint_pubkey)
         .await
         .map_err(|e| MigrateError::MigrationFailed(nft_mint.clone(), e.to_string()))?;
-    let collection_metadata =
-        <Metadata as BorshDeserialize>::deserialize(&mut collection_md_account.as_slice())
-            .map_err(|e| MigrateError::MigrationFailed(nft_mint.clone(), e.to_string()))?;
 
     let set_and_verify_ix = if collection_metadata.collection_details.is_some() {
         set_and_verify_sized_collection_item(
@@ -160,7 +156,7 @@ pub async fn migrate_collection<'a>(args: &MigrateArgs<'a>) -> AnyResult<()> {
     let keypair = Arc::new(Keypair::from_base58_string(&args.keypair.to_base58_string()));
 
     let mut mint_accounts = if let Some(candy_machine_id) = args.candy_machine_id.clone() {
-        get_mint_accounts(&args.client, &Some(candy_machine_id), 0, None, false, true).await?
+        get_mint_accounts(args.client, &Some(candy_machine_id), 0, None, false, true).await?
     } else if let Some(mint_list) = args.mint_list.clone() {
         mint_list
     } else {
@@ -188,7 +184,7 @@ pub async fn migrate_collection<'a>(args: &MigrateArgs<'a>) -> AnyResult<()> {
             let mint_address = args.mint_address.clone();
 
             let _permit = permit;
-            match set_and_verify(&async_client, keypair, mint, mint_address, false).await {
+            match set_and_verify(*async_client, keypair, mint, mint_address, false).await {
                 Ok(_) => {}
                 Err(e) => {
                     migrate_failed.push(e);
@@ -3,7 +3,9 @@ mod data;
 mod items;
 mod methods;
 mod migrate;
+mod sized;
 pub use data::*;
 pub use items::*;
 pub use methods::*;
 pub use migrate::*;
+pub use sized::*;
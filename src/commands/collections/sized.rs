@@ -0,0 +1,110 @@
+use super::common::*;
+use crate::commands::decode::{decode, deserialize_metadata};
+use crate::constants::MAX_MULTIPLE_ACCOUNTS;
+use crate::derive::derive_metadata_pda;
+use crate::utils::async_send_and_confirm_transaction;
+use mpl_token_metadata::instruction::set_collection_size;
+use std::collections::HashSet;
+
+pub struct SizeCollectionArgs<'a> {
+    pub client: &'a dyn AccountSource,
+    pub keypair: Arc<Keypair>,
+    pub collection_mint: Pubkey,
+    /// Every item of the collection; only those verified to it are counted.
+    pub mint_list: Vec<String>,
+}
+
+/// Converts an unsized collection parent to a sized one, counting its verified items from
+/// `mint_list`. Returns the size that was set.
+pub async fn convert_to_sized_collection<'a>(args: &SizeCollectionArgs<'a>) -> AnyResult<u64> {
+    let collection_metadata = decode(args.client, &args.collection_mint).await?;
+
+    if collection_metadata.collection_details.is_some() {
+        return Err(anyhow!(
+            "Collection {} is already sized",
+            args.collection_mint
+        ));
+    }
+
+    let size = count_verified_items(args.client, &args.collection_mint, &args.mint_list).await?;
+    info!(
+        "Setting size of collection {} to {}",
+        args.collection_mint, size
+    );
+
+    let set_collection_size_ix = set_collection_size(
+        metadata_program_id(),
+        derive_metadata_pda(&args.collection_mint),
+        args.keypair.pubkey(),
+        args.collection_mint,
+        None,
+        size,
+    );
+
+    async_send_and_confirm_transaction(
+        args.client,
+        args.keypair.clone(),
+        &[set_collection_size_ix],
+    )
+    .await?;
+
+    Ok(size)
+}
+
+/// Counts the mints in `mint_list` whose metadata is verified to `collection_mint`.
+/// Missing accounts and duplicate mints are not counted.
+pub async fn count_verified_items(
+    client: &dyn AccountSource,
+    collection_mint: &Pubkey,
+    mint_list: &[String],
+) -> AnyResult<u64> {
+    let mints = mint_list
+        .iter()
+        .map(|m| Pubkey::from_str(m))
+        .collect::<Result<HashSet<_>, _>>()?;
+    let metadata_pubkeys: Vec<Pubkey> = mints.iter().map(derive_metadata_pda).collect();
+
+    let mut size = 0;
+
+    for chunk in metadata_pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = client.get_multiple_accounts(chunk).await?;
+
+        size += accounts
+            .iter()
+            .flatten()
+            .filter_map(|account| deserialize_metadata(&account.data).ok())
+            .filter(|metadata| {
+                metadata
+                    .collection
+                    .as_ref()
+                    .map(|c| c.verified && c.key == *collection_mint)
+                    .unwrap_or(false)
+            })
+            .count() as u64;
+    }
+
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account_source::fixtures::*;
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_count_verified_items() {
+        let source = fixture_source();
+        let mint_list = vec![
+            MINT.to_string(),
+            MINT.to_string(),
+            Pubkey::new_unique().to_string(),
+        ];
+
+        let size = block_on(count_verified_items(&source, &COLLECTION_MINT, &mint_list)).unwrap();
+        assert_eq!(size, 1);
+
+        let size = block_on(count_verified_items(&source, &MINT, &mint_list)).unwrap();
+        assert_eq!(size, 0);
+    }
+}
@@ -1,50 +1,275 @@
 use anyhow::{anyhow, Result as AnyResult};
-use borsh::BorshDeserialize;
+use indexmap::IndexMap;
 use mpl_token_metadata::state::Metadata;
-use mpl_token_metadata::state::{Edition, EditionMarker, MasterEditionV2};
-use serde::Serialize;
-use solana_client::nonblocking::rpc_client::RpcClient;
+use mpl_token_metadata::state::{
+    Collection, CollectionAuthorityRecord, CollectionDetails, Creator, Edition, EditionMarker,
+    EditionMarkerV2, Key, MasterEditionV1, MasterEditionV2, TokenRecord, UseAuthorityRecord, Uses,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_client::client_error::{ClientError, ClientErrorKind};
 use solana_program::borsh::try_from_slice_unchecked;
 use solana_sdk::pubkey::Pubkey;
-use std::str::FromStr;
+use std::{io, io::Write, str::FromStr};
 
+use crate::account_source::AccountSource;
 use crate::constants::*;
 use crate::derive::*;
 use crate::errors::*;
+use crate::output::{write_records, OutputFormat};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct JSONCreator {
     pub address: String,
     pub verified: bool,
     pub share: u8,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct JSONCollection {
     pub verified: bool,
     pub key: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum JSONCollectionDetails {
     V1 { size: u64 },
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct JSONUses {
     pub use_method: String,
     pub remaining: u64,
     pub total: u64,
 }
 
-pub async fn decode_master_edition(client: &RpcClient, mint_account: &str) -> AnyResult<()> {
+#[derive(Debug, Serialize, Deserialize)]
+pub enum JSONEdition {
+    Master {
+        supply: u64,
+        max_supply: Option<u64>,
+    },
+    Print {
+        parent: String,
+        edition: u64,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JSONMetadata {
+    pub mint: String,
+    pub update_authority: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<JSONCreator>>,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<String>,
+    pub collection: Option<JSONCollection>,
+    pub uses: Option<JSONUses>,
+    pub collection_details: Option<JSONCollectionDetails>,
+    pub edition: Option<JSONEdition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub off_chain: Option<Value>,
+}
+
+/// A token metadata account decoded according to its `Key` discriminator.
+#[derive(Debug, Serialize)]
+pub enum DecodedAccount {
+    Metadata(Metadata),
+    MasterEditionV1(MasterEditionV1),
+    MasterEditionV2(MasterEditionV2),
+    Edition(Edition),
+    EditionMarker(EditionMarker),
+    EditionMarkerV2(EditionMarkerV2),
+    CollectionAuthorityRecord(CollectionAuthorityRecord),
+    UseAuthorityRecord(UseAuthorityRecord),
+    TokenRecord(TokenRecord),
+}
+
+/// Per-mint entry of a batch decode output file.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum JSONDecodeResult<'a> {
+    Metadata(&'a JSONMetadata),
+    Error { mint: &'a str, error: String },
+}
+
+pub type DecodeResults = IndexMap<String, Result<JSONMetadata, DecodeError>>;
+
+/// A batch decode result flattened into fixed CSV columns, empty where they don't apply.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DecodeCsvRecord {
+    pub mint: String,
+    /// `ok` or `error`.
+    pub status: String,
+    pub error: Option<String>,
+    pub update_authority: Option<String>,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub uri: Option<String>,
+    pub seller_fee_basis_points: Option<u16>,
+    /// `address:share:verified` entries separated by `;`.
+    pub creators: Option<String>,
+    pub collection: Option<String>,
+    pub collection_verified: Option<bool>,
+    pub primary_sale_happened: Option<bool>,
+    pub is_mutable: Option<bool>,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<String>,
+    pub edition_type: Option<String>,
+    pub edition: Option<u64>,
+    pub max_supply: Option<u64>,
+}
+
+impl DecodeCsvRecord {
+    pub fn new(mint: &str, result: &Result<JSONMetadata, DecodeError>) -> Self {
+        let metadata = match result {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                return Self {
+                    mint: mint.to_string(),
+                    status: "error".to_string(),
+                    error: Some(err.to_string()),
+                    ..Default::default()
+                }
+            }
+        };
+
+        let creators = metadata.creators.as_ref().map(|creators| {
+            creators
+                .iter()
+                .map(|c| format!("{}:{}:{}", c.address, c.share, c.verified))
+                .collect::<Vec<_>>()
+                .join(";")
+        });
+
+        let (edition_type, edition, max_supply) = match metadata.edition {
+            Some(JSONEdition::Master { supply, max_supply }) => {
+                (Some("master".to_string()), Some(supply), max_supply)
+            }
+            Some(JSONEdition::Print { edition, .. }) => {
+                (Some("print".to_string()), Some(edition), None)
+            }
+            None => (None, None, None),
+        };
+
+        Self {
+            mint: mint.to_string(),
+            status: "ok".to_string(),
+            error: None,
+            update_authority: Some(metadata.update_authority.clone()),
+            name: Some(metadata.name.clone()),
+            symbol: Some(metadata.symbol.clone()),
+            uri: Some(metadata.uri.clone()),
+            seller_fee_basis_points: Some(metadata.seller_fee_basis_points),
+            creators,
+            collection: metadata.collection.as_ref().map(|c| c.key.clone()),
+            collection_verified: metadata.collection.as_ref().map(|c| c.verified),
+            primary_sale_happened: Some(metadata.primary_sale_happened),
+            is_mutable: Some(metadata.is_mutable),
+            edition_nonce: metadata.edition_nonce,
+            token_standard: metadata.token_standard.clone(),
+            edition_type,
+            edition,
+            max_supply,
+        }
+    }
+}
+
+impl From<&Creator> for JSONCreator {
+    fn from(creator: &Creator) -> Self {
+        Self {
+            address: creator.address.to_string(),
+            verified: creator.verified,
+            share: creator.share,
+        }
+    }
+}
+
+impl From<&Collection> for JSONCollection {
+    fn from(collection: &Collection) -> Self {
+        Self {
+            verified: collection.verified,
+            key: collection.key.to_string(),
+        }
+    }
+}
+
+impl From<&CollectionDetails> for JSONCollectionDetails {
+    fn from(details: &CollectionDetails) -> Self {
+        #[allow(deprecated)]
+        match details {
+            CollectionDetails::V1 { size } => JSONCollectionDetails::V1 { size: *size },
+        }
+    }
+}
+
+impl From<&Uses> for JSONUses {
+    fn from(uses: &Uses) -> Self {
+        Self {
+            use_method: format!("{:?}", uses.use_method),
+            remaining: uses.remaining,
+            total: uses.total,
+        }
+    }
+}
+
+impl From<&Metadata> for JSONMetadata {
+    fn from(md: &Metadata) -> Self {
+        Self {
+            mint: md.mint.to_string(),
+            update_authority: md.update_authority.to_string(),
+            name: md.data.name.trim_matches(char::from(0)).to_string(),
+            symbol: md.data.symbol.trim_matches(char::from(0)).to_string(),
+            uri: md.data.uri.trim_matches(char::from(0)).to_string(),
+            seller_fee_basis_points: md.data.seller_fee_basis_points,
+            creators: md
+                .data
+                .creators
+                .as_ref()
+                .map(|creators| creators.iter().map(JSONCreator::from).collect()),
+            primary_sale_happened: md.primary_sale_happened,
+            is_mutable: md.is_mutable,
+            edition_nonce: md.edition_nonce,
+            token_standard: md.token_standard.as_ref().map(|ts| format!("{:?}", ts)),
+            collection: md.collection.as_ref().map(JSONCollection::from),
+            uses: md.uses.as_ref().map(JSONUses::from),
+            collection_details: md
+                .collection_details
+                .as_ref()
+                .map(JSONCollectionDetails::from),
+            edition: None,
+            off_chain: None,
+        }
+    }
+}
+
+pub async fn decode_master_edition(
+    client: &dyn AccountSource,
+    mint_account: &str,
+) -> AnyResult<()> {
     let master_edition = decode_master_edition_from_mint(client, mint_account).await?;
     log::debug!("{:?}", master_edition);
 
     Ok(())
 }
 
-pub async fn decode_print_edition(client: &RpcClient, mint_account: &str) -> AnyResult<()> {
+pub async fn decode_metadata(
+    client: &dyn AccountSource,
+    mint_account: &str,
+    full: bool,
+) -> AnyResult<JSONMetadata> {
+    let metadata = decode_metadata_json_from_mint(client, mint_account, full).await?;
+    log::debug!("{}", serde_json::to_string_pretty(&metadata)?);
+
+    Ok(metadata)
+}
+
+pub async fn decode_print_edition(client: &dyn AccountSource, mint_account: &str) -> AnyResult<()> {
     let print_edition = decode_edition_from_mint(client, mint_account).await?;
     log::debug!("{:?}", print_edition);
 
@@ -52,7 +277,7 @@ pub async fn decode_print_edition(client: &RpcClient, mint_account: &str) -> Any
 }
 
 pub async fn decode_edition_marker(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     mint_account: &str,
     edition_num: Option<u64>,
     marker_num: Option<u64>,
@@ -71,92 +296,81 @@ pub async fn decode_edition_marker(
     Ok(())
 }
 
-pub async fn decode_raw(client: &RpcClient, mint_account: &str) -> Result<Vec<u8>, DecodeError> {
+pub async fn decode_raw(
+    client: &dyn AccountSource,
+    mint_account: &str,
+) -> Result<Vec<u8>, DecodeError> {
     let pubkey = match Pubkey::from_str(mint_account) {
         Ok(pubkey) => pubkey,
         Err(_) => return Err(DecodeError::PubkeyParseFailed(mint_account.to_string())),
     };
-    let metadata_pda = get_metadata_pda(&pubkey);
-
-    // let account_data = match retry(
-    //     Exponential::from_millis_with_factor(250, 2.0).take(3),
-    //     || client.get_account_data(&metadata_pda),
-    // ) {
-    //     Ok(data) => data,
-    //     Err(err) => {
-    //         return Err(DecodeError::NetworkError(err.to_string()));
-    //     }
-    // };
-    let account_data = match client.get_account_data(&metadata_pda).await {
-        Ok(data) => data,
-        Err(err) => {
-            return Err(DecodeError::NetworkError(err.to_string()));
-        }
-    };
+    let metadata_pda = derive_metadata_pda(&pubkey);
 
-    Ok(account_data)
+    get_account_data(client, &metadata_pda).await
 }
 
-pub async fn decode(client: &RpcClient, pubkey: &Pubkey) -> Result<Metadata, DecodeError> {
-    let metadata_pda = get_metadata_pda(pubkey);
-
-    let account_data = match client.get_account_data(&metadata_pda).await {
-        Ok(data) => data,
-        Err(err) => {
-            return Err(DecodeError::NetworkError(err.to_string()));
-        }
-    };
+/// Loads and decodes the metadata account of a mint.
+///
+/// This is the single metadata loader used across the crate, see `deserialize_metadata`
+/// for the decoding policy.
+pub async fn decode(client: &dyn AccountSource, pubkey: &Pubkey) -> Result<Metadata, DecodeError> {
+    let metadata_pda = derive_metadata_pda(pubkey);
+    let account_data = get_account_data(client, &metadata_pda).await?;
 
-    let metadata: Metadata = match Metadata::deserialize(&mut account_data.as_slice()) {
-        Ok(m) => m,
-        Err(err) => return Err(DecodeError::DecodeMetadataFailed(err.to_string())),
-    };
-
-    Ok(metadata)
+    deserialize_metadata(&account_data)
+        .map_err(|err| DecodeError::DecodeMetadataFailed(err.to_string()))
 }
 
-pub fn get_metadata_pda(pubkey: &Pubkey) -> Pubkey {
-    let metaplex_pubkey = METAPLEX_PROGRAM_ID
-        .parse::<Pubkey>()
-        .expect("Failed to parse Metaplex Program Id");
-
-    let seeds = &[
-        "metadata".as_bytes(),
-        metaplex_pubkey.as_ref(),
-        pubkey.as_ref(),
-    ];
-
-    let (pda, _) = Pubkey::find_program_address(seeds, &metaplex_pubkey);
-    pda
+/// Decodes metadata account data.
+///
+/// The account key must be `MetadataV1`, but trailing bytes after the last field are
+/// ignored so older accounts with padding decode the same way as current ones.
+pub fn deserialize_metadata(data: &[u8]) -> io::Result<Metadata> {
+    match data.first() {
+        Some(k) if *k == Key::MetadataV1 as u8 => try_from_slice_unchecked(data),
+        Some(k) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("expected a metadata account, found account key {}", k),
+        )),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "account data is empty",
+        )),
+    }
 }
 
 pub async fn decode_metadata_from_mint(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     mint_address: &str,
 ) -> Result<Metadata, DecodeError> {
     let pubkey = match Pubkey::from_str(mint_address) {
         Ok(pubkey) => pubkey,
         Err(_) => return Err(DecodeError::PubkeyParseFailed(mint_address.to_string())),
     };
-    let metadata_pda = derive_metadata_pda(&pubkey);
 
-    let account_data = match client.get_account_data(&metadata_pda).await {
-        Ok(data) => data,
-        Err(err) => {
-            return Err(DecodeError::ClientError(err.kind));
-        }
-    };
+    decode(client, &pubkey).await
+}
 
-    let metadata: Metadata = match try_from_slice_unchecked(&account_data) {
-        Ok(m) => m,
-        Err(err) => return Err(DecodeError::DecodeMetadataFailed(err.to_string())),
-    };
+async fn get_account_data(
+    client: &dyn AccountSource,
+    pubkey: &Pubkey,
+) -> Result<Vec<u8>, DecodeError> {
+    match client.get_account(pubkey).await {
+        Ok(Some(account)) => Ok(account.data),
+        Ok(None) => Err(DecodeError::MissingAccount(pubkey.to_string())),
+        Err(err) => Err(client_error(err)),
+    }
+}
 
-    Ok(metadata)
+fn client_error(err: anyhow::Error) -> DecodeError {
+    match err.downcast::<ClientError>() {
+        Ok(err) => DecodeError::ClientError(err.kind),
+        Err(err) => DecodeError::ClientError(ClientErrorKind::Custom(err.to_string())),
+    }
 }
 
 pub async fn decode_master_edition_from_mint(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     mint_address: &str,
 ) -> Result<MasterEditionV2, DecodeError> {
     let pubkey = match Pubkey::from_str(mint_address) {
@@ -166,12 +380,7 @@ pub async fn decode_master_edition_from_mint(
 
     let edition_pda = derive_edition_pda(&pubkey);
 
-    let account_data = match client.get_account_data(&edition_pda).await {
-        Ok(data) => data,
-        Err(err) => {
-            return Err(DecodeError::ClientError(err.kind));
-        }
-    };
+    let account_data = get_account_data(client, &edition_pda).await?;
 
     let master_edition: MasterEditionV2 = match try_from_slice_unchecked(&account_data) {
         Ok(e) => e,
@@ -182,7 +391,7 @@ pub async fn decode_master_edition_from_mint(
 }
 
 pub async fn decode_edition_from_mint(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     mint_address: &str,
 ) -> Result<Edition, DecodeError> {
     let pubkey = match Pubkey::from_str(mint_address) {
@@ -192,12 +401,7 @@ pub async fn decode_edition_from_mint(
 
     let edition_pda = derive_edition_pda(&pubkey);
 
-    let account_data = match client.get_account_data(&edition_pda).await {
-        Ok(data) => data,
-        Err(err) => {
-            return Err(DecodeError::ClientError(err.kind));
-        }
-    };
+    let account_data = get_account_data(client, &edition_pda).await?;
 
     let edition: Edition = match try_from_slice_unchecked(&account_data) {
         Ok(e) => e,
@@ -208,7 +412,7 @@ pub async fn decode_edition_from_mint(
 }
 
 pub async fn decode_edition_marker_from_mint(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     mint_address: &str,
     edition_num: u64,
 ) -> Result<EditionMarker, DecodeError> {
@@ -219,12 +423,7 @@ pub async fn decode_edition_marker_from_mint(
 
     let edition_marker_pda = derive_edition_marker_pda(&pubkey, edition_num);
 
-    let account_data = match client.get_account_data(&edition_marker_pda).await {
-        Ok(data) => data,
-        Err(err) => {
-            return Err(DecodeError::ClientError(err.kind));
-        }
-    };
+    let account_data = get_account_data(client, &edition_marker_pda).await?;
 
     let edition_marker: EditionMarker = match try_from_slice_unchecked(&account_data) {
         Ok(e) => e,
@@ -233,3 +432,543 @@ pub async fn decode_edition_marker_from_mint(
 
     Ok(edition_marker)
 }
+
+/// Decodes the metadata and edition of a mint into its JSON representation.
+///
+/// With `full` set, the off-chain JSON at the metadata `uri` is fetched and embedded as well.
+pub async fn decode_metadata_json_from_mint(
+    client: &dyn AccountSource,
+    mint_address: &str,
+    full: bool,
+) -> Result<JSONMetadata, DecodeError> {
+    let metadata = decode_metadata_from_mint(client, mint_address).await?;
+    let mut json_metadata = JSONMetadata::from(&metadata);
+
+    let edition_pda = derive_edition_pda(&metadata.mint);
+    let edition_account = client
+        .get_account(&edition_pda)
+        .await
+        .map_err(client_error)?;
+
+    if let Some(account) = edition_account {
+        json_metadata.edition = decode_edition_json(&account.data)
+            .map_err(|err| DecodeError::DecodeMetadataFailed(err.to_string()))?;
+    }
+
+    if full {
+        let http_client = reqwest::Client::new();
+        json_metadata.off_chain =
+            Some(fetch_off_chain_json(&http_client, &json_metadata.uri).await?);
+    }
+
+    Ok(json_metadata)
+}
+
+/// Decodes master or print edition account data; returns `None` for other account types.
+pub fn decode_edition_json(data: &[u8]) -> io::Result<Option<JSONEdition>> {
+    let edition = match data.first() {
+        Some(k) if *k == Key::MasterEditionV2 as u8 => {
+            let master_edition: MasterEditionV2 = try_from_slice_unchecked(data)?;
+            Some(JSONEdition::Master {
+                supply: master_edition.supply,
+                max_supply: master_edition.max_supply,
+            })
+        }
+        Some(k) if *k == Key::MasterEditionV1 as u8 => {
+            let master_edition: MasterEditionV1 = try_from_slice_unchecked(data)?;
+            Some(JSONEdition::Master {
+                supply: master_edition.supply,
+                max_supply: master_edition.max_supply,
+            })
+        }
+        Some(k) if *k == Key::EditionV1 as u8 => {
+            let edition: Edition = try_from_slice_unchecked(data)?;
+            Some(JSONEdition::Print {
+                parent: edition.parent.to_string(),
+                edition: edition.edition,
+            })
+        }
+        _ => None,
+    };
+
+    Ok(edition)
+}
+
+pub async fn fetch_off_chain_json(
+    http_client: &reqwest::Client,
+    uri: &str,
+) -> Result<Value, DecodeError> {
+    let response = http_client
+        .get(uri)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|err| DecodeError::OffChainFetchFailed(err.to_string()))?;
+
+    let off_chain = response
+        .json::<Value>()
+        .await
+        .map_err(|err| DecodeError::OffChainFetchFailed(err.to_string()))?;
+
+    Ok(off_chain)
+}
+
+/// Decodes the metadata of many mints, fetching accounts in chunks with `getMultipleAccounts`.
+///
+/// Results are keyed by mint address in the order given. As with `decode`, a
+/// `MissingAccount` error names the metadata account that wasn't found. With
+/// `with_editions` set the master or print edition of each mint is fetched and decoded as
+/// well.
+pub async fn decode_metadata_batch(
+    client: &dyn AccountSource,
+    mint_list: &[String],
+    with_editions: bool,
+) -> DecodeResults {
+    let mut results: DecodeResults = IndexMap::new();
+    let mut mints = Vec::new();
+
+    for mint_address in mint_list {
+        match Pubkey::from_str(mint_address) {
+            Ok(pubkey) => mints.push((mint_address.clone(), pubkey)),
+            Err(_) => {
+                results.insert(
+                    mint_address.clone(),
+                    Err(DecodeError::PubkeyParseFailed(mint_address.clone())),
+                );
+            }
+        }
+    }
+
+    for chunk in mints.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let metadata_pdas: Vec<Pubkey> = chunk
+            .iter()
+            .map(|(_, mint)| derive_metadata_pda(mint))
+            .collect();
+
+        let metadata_accounts = match client.get_multiple_accounts(&metadata_pdas).await {
+            Ok(accounts) => accounts,
+            Err(err) => {
+                log::error!("Failed to fetch metadata accounts: {}", err);
+                for (mint_address, _) in chunk {
+                    results.insert(
+                        mint_address.clone(),
+                        Err(DecodeError::NetworkError(err.to_string())),
+                    );
+                }
+                continue;
+            }
+        };
+
+        let edition_accounts = if with_editions {
+            let edition_pdas: Vec<Pubkey> = chunk
+                .iter()
+                .map(|(_, mint)| derive_edition_pda(mint))
+                .collect();
+
+            match client.get_multiple_accounts(&edition_pdas).await {
+                Ok(accounts) => Some(accounts),
+                Err(err) => {
+                    log::error!("Failed to fetch edition accounts: {}", err);
+                    for (mint_address, _) in chunk {
+                        results.insert(
+                            mint_address.clone(),
+                            Err(DecodeError::NetworkError(err.to_string())),
+                        );
+                    }
+                    continue;
+                }
+            }
+        } else {
+            None
+        };
+
+        for (i, (mint_address, _)) in chunk.iter().enumerate() {
+            let edition_data = edition_accounts
+                .as_ref()
+                .and_then(|accounts| accounts[i].as_ref())
+                .map(|account| account.data.as_slice());
+
+            let result = match metadata_accounts[i] {
+                Some(ref account) => decode_metadata_json(&account.data, edition_data)
+                    .map_err(|err| DecodeError::DecodeMetadataFailed(err.to_string())),
+                None => Err(DecodeError::MissingAccount(metadata_pdas[i].to_string())),
+            };
+
+            results.insert(mint_address.clone(), result);
+        }
+    }
+
+    results
+}
+
+fn decode_metadata_json(
+    metadata_data: &[u8],
+    edition_data: Option<&[u8]>,
+) -> io::Result<JSONMetadata> {
+    let metadata = deserialize_metadata(metadata_data)?;
+    let mut json_metadata = JSONMetadata::from(&metadata);
+
+    if let Some(data) = edition_data {
+        json_metadata.edition = decode_edition_json(data)?;
+    }
+
+    Ok(json_metadata)
+}
+
+/// Writes batch decode results as a map of mint to metadata or error for JSON, or as one
+/// record per mint for NDJSON and CSV.
+pub fn write_decode_results<W: Write>(
+    writer: W,
+    results: &DecodeResults,
+    format: OutputFormat,
+) -> AnyResult<()> {
+    let entries = results.iter().map(|(mint, result)| {
+        let entry = match result {
+            Ok(metadata) => JSONDecodeResult::Metadata(metadata),
+            Err(err) => JSONDecodeResult::Error {
+                mint,
+                error: err.to_string(),
+            },
+        };
+        (mint, entry)
+    });
+
+    match format {
+        OutputFormat::Pretty => {
+            let output: IndexMap<&String, JSONDecodeResult> = entries.collect();
+            serde_json::to_writer_pretty(writer, &output)?;
+        }
+        OutputFormat::Compact => {
+            let output: IndexMap<&String, JSONDecodeResult> = entries.collect();
+            serde_json::to_writer(writer, &output)?;
+        }
+        OutputFormat::Ndjson => {
+            let output: Vec<JSONDecodeResult> = entries.map(|(_, entry)| entry).collect();
+            write_records(writer, &output, format)?;
+        }
+        OutputFormat::Csv => {
+            let rows: Vec<DecodeCsvRecord> = results
+                .iter()
+                .map(|(mint, result)| DecodeCsvRecord::new(mint, result))
+                .collect();
+            write_records(writer, &rows, format)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads any token metadata account by address and decodes it as the type its key byte names.
+pub async fn decode_account(
+    client: &dyn AccountSource,
+    address: &str,
+) -> Result<DecodedAccount, DecodeError> {
+    let pubkey = match Pubkey::from_str(address) {
+        Ok(pubkey) => pubkey,
+        Err(_) => return Err(DecodeError::PubkeyParseFailed(address.to_string())),
+    };
+
+    let account = client
+        .get_account(&pubkey)
+        .await
+        .map_err(client_error)?
+        .ok_or_else(|| DecodeError::MissingAccount(address.to_string()))?;
+
+    if account.owner != mpl_token_metadata::id() {
+        return Err(DecodeError::UnsupportedAccount(format!(
+            "{} is owned by {}, not the token metadata program",
+            address, account.owner
+        )));
+    }
+
+    decode_account_data(&account.data)
+        .map_err(|err| DecodeError::UnsupportedAccount(format!("{}: {}", address, err)))
+}
+
+pub fn decode_account_data(data: &[u8]) -> io::Result<DecodedAccount> {
+    let key = *data
+        .first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "account data is empty"))?;
+
+    let decoded = match key {
+        k if k == Key::MetadataV1 as u8 => DecodedAccount::Metadata(deserialize_metadata(data)?),
+        k if k == Key::MasterEditionV1 as u8 => {
+            DecodedAccount::MasterEditionV1(try_from_slice_unchecked(data)?)
+        }
+        k if k == Key::MasterEditionV2 as u8 => {
+            DecodedAccount::MasterEditionV2(try_from_slice_unchecked(data)?)
+        }
+        k if k == Key::EditionV1 as u8 => DecodedAccount::Edition(try_from_slice_unchecked(data)?),
+        k if k == Key::EditionMarker as u8 => {
+            DecodedAccount::EditionMarker(try_from_slice_unchecked(data)?)
+        }
+        k if k == Key::EditionMarkerV2 as u8 => {
+            DecodedAccount::EditionMarkerV2(try_from_slice_unchecked(data)?)
+        }
+        k if k == Key::CollectionAuthorityRecord as u8 => {
+            DecodedAccount::CollectionAuthorityRecord(try_from_slice_unchecked(data)?)
+        }
+        k if k == Key::UseAuthorityRecord as u8 => {
+            DecodedAccount::UseAuthorityRecord(try_from_slice_unchecked(data)?)
+        }
+        k if k == Key::TokenRecord as u8 => {
+            DecodedAccount::TokenRecord(try_from_slice_unchecked(data)?)
+        }
+        k => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported account key {}", k),
+            ))
+        }
+    };
+
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    #[test]
+    fn test_decode_account_data_detects_type() {
+        let master_edition = MasterEditionV2 {
+            key: Key::MasterEditionV2,
+            supply: 3,
+            max_supply: Some(10),
+        };
+        let data = master_edition.try_to_vec().unwrap();
+
+        match decode_account_data(&data).unwrap() {
+            DecodedAccount::MasterEditionV2(decoded) => assert_eq!(decoded, master_edition),
+            other => panic!("decoded as the wrong type: {:?}", other),
+        }
+
+        let edition = Edition {
+            key: Key::EditionV1,
+            parent: Pubkey::new_unique(),
+            edition: 7,
+        };
+        let data = edition.try_to_vec().unwrap();
+
+        match decode_account_data(&data).unwrap() {
+            DecodedAccount::Edition(decoded) => assert_eq!(decoded, edition),
+            other => panic!("decoded as the wrong type: {:?}", other),
+        }
+
+        assert!(decode_account_data(&[]).is_err());
+        assert!(decode_account_data(&[Key::ReservationListV2 as u8]).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_metadata_ignores_padding() {
+        let metadata = Metadata {
+            key: Key::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            data: mpl_token_metadata::state::Data {
+                name: "Test".to_string(),
+                symbol: "TST".to_string(),
+                uri: "https://example.com/0.json".to_string(),
+                seller_fee_basis_points: 500,
+                creators: None,
+            },
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: Some(255),
+            token_standard: None,
+            collection: None,
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        };
+        let mut data = metadata.try_to_vec().unwrap();
+        data.extend_from_slice(&[0; 64]);
+
+        let decoded = deserialize_metadata(&data).unwrap();
+        assert_eq!(decoded.mint, metadata.mint);
+        assert_eq!(decoded.data.uri, metadata.data.uri);
+
+        data[0] = Key::MasterEditionV2 as u8;
+        assert!(deserialize_metadata(&data).is_err());
+    }
+
+    #[test]
+    fn test_decode_from_account_source() {
+        use crate::account_source::fixtures::*;
+        use futures::executor::block_on;
+        use solana_sdk::signer::Signer;
+
+        let source = fixture_source();
+
+        let metadata = block_on(decode(&source, &MINT)).unwrap();
+        assert_eq!(metadata.data.name.trim_end_matches('\0'), "Fixture #1");
+        assert_eq!(metadata.update_authority, update_authority().pubkey());
+
+        let metadata_pda = derive_metadata_pda(&MINT).to_string();
+        assert!(matches!(
+            block_on(decode_account(&source, &metadata_pda)).unwrap(),
+            DecodedAccount::Metadata(_)
+        ));
+
+        assert!(matches!(
+            block_on(decode(&source, &COLLECTION_MINT)),
+            Err(DecodeError::MissingAccount(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_decode_results_csv() {
+        let metadata = Metadata {
+            key: Key::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            data: mpl_token_metadata::state::Data {
+                name: "Item #1".to_string(),
+                symbol: "ITM".to_string(),
+                uri: "https://example.com/1.json".to_string(),
+                seller_fee_basis_points: 500,
+                creators: None,
+            },
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: None,
+            collection: None,
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        };
+        let mut results: DecodeResults = IndexMap::new();
+        results.insert(
+            "A".to_string(),
+            Err(DecodeError::MissingAccount("A".to_string())),
+        );
+        results.insert("B".to_string(), Ok(JSONMetadata::from(&metadata)));
+
+        let mut buf = Vec::new();
+        write_decode_results(&mut buf, &results, OutputFormat::Csv).unwrap();
+        let mut reader = csv::Reader::from_reader(buf.as_slice());
+        let rows: Vec<DecodeCsvRecord> = reader.deserialize().map(|r| r.unwrap()).collect();
+
+        assert_eq!(rows[0].status, "error");
+        assert_eq!(
+            rows[0].error.as_deref(),
+            Some("no account data found for A")
+        );
+        assert_eq!(rows[1].status, "ok");
+        assert_eq!(rows[1].name.as_deref(), Some("Item #1"));
+        assert!(rows[1].error.is_none());
+    }
+
+    #[test]
+    fn test_metadata_to_json() {
+        let creator = Pubkey::new_unique();
+        let collection = Pubkey::new_unique();
+        let metadata = Metadata {
+            key: Key::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            data: mpl_token_metadata::state::Data {
+                name: format!("{:\0<32}", "Item #1"),
+                symbol: format!("{:\0<10}", "ITM"),
+                uri: format!("{:\0<200}", "https://example.com/1.json"),
+                seller_fee_basis_points: 500,
+                creators: Some(vec![Creator {
+                    address: creator,
+                    verified: true,
+                    share: 100,
+                }]),
+            },
+            primary_sale_happened: true,
+            is_mutable: false,
+            edition_nonce: Some(254),
+            token_standard: None,
+            collection: Some(Collection {
+                verified: true,
+                key: collection,
+            }),
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        };
+        let master_edition = MasterEditionV2 {
+            key: Key::MasterEditionV2,
+            supply: 3,
+            max_supply: Some(10),
+        };
+
+        let json = decode_metadata_json(
+            &metadata.try_to_vec().unwrap(),
+            Some(&master_edition.try_to_vec().unwrap()),
+        )
+        .unwrap();
+        assert_eq!(json.mint, metadata.mint.to_string());
+        assert_eq!(json.name, "Item #1");
+        assert_eq!(json.symbol, "ITM");
+        assert_eq!(json.uri, "https://example.com/1.json");
+
+        let creators = json.creators.as_ref().unwrap();
+        assert_eq!(creators.len(), 1);
+        assert_eq!(creators[0].address, creator.to_string());
+        assert!(creators[0].verified);
+        assert_eq!(creators[0].share, 100);
+
+        let json_collection = json.collection.as_ref().unwrap();
+        assert_eq!(json_collection.key, collection.to_string());
+        assert!(json_collection.verified);
+        assert!(matches!(
+            json.edition,
+            Some(JSONEdition::Master {
+                supply: 3,
+                max_supply: Some(10)
+            })
+        ));
+
+        let edition = Edition {
+            key: Key::EditionV1,
+            parent: Pubkey::new_unique(),
+            edition: 7,
+        };
+        let json = decode_metadata_json(
+            &metadata.try_to_vec().unwrap(),
+            Some(&edition.try_to_vec().unwrap()),
+        )
+        .unwrap();
+        match json.edition {
+            Some(JSONEdition::Print { parent, edition: 7 }) => {
+                assert_eq!(parent, edition.parent.to_string())
+            }
+            other => panic!("wrong edition: {:?}", other),
+        }
+
+        let json = decode_metadata_json(&metadata.try_to_vec().unwrap(), None).unwrap();
+        assert!(json.edition.is_none());
+    }
+
+    #[test]
+    fn test_missing_account_names_metadata_pda() {
+        use crate::account_source::fixtures::*;
+        use futures::executor::block_on;
+
+        let source = fixture_source();
+        let metadata_pda = derive_metadata_pda(&COLLECTION_MINT).to_string();
+
+        match block_on(decode(&source, &COLLECTION_MINT)) {
+            Err(DecodeError::MissingAccount(address)) => assert_eq!(address, metadata_pda),
+            other => panic!("expected a missing account, got {:?}", other),
+        }
+
+        let mints = vec![MINT.to_string(), COLLECTION_MINT.to_string()];
+        let results = block_on(decode_metadata_batch(&source, &mints, false));
+        assert_eq!(
+            results.keys().collect::<Vec<_>>(),
+            mints.iter().collect::<Vec<_>>()
+        );
+        assert!(results[&MINT.to_string()].is_ok());
+        match &results[&COLLECTION_MINT.to_string()] {
+            Err(DecodeError::MissingAccount(address)) => assert_eq!(*address, metadata_pda),
+            other => panic!("expected a missing account, got {:?}", other),
+        }
+    }
+}
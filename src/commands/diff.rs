@@ -0,0 +1,335 @@
+use anyhow::{anyhow, Result as AnyResult};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+use crate::account_source::AccountSource;
+use crate::commands::decode::{decode_metadata_batch, DecodeCsvRecord, JSONMetadata};
+use crate::commands::update::read_update_data;
+use crate::output::OutputFormat;
+
+/// Fields compared by `diff_metadata`, using their `JSONMetadata` names.
+pub const DIFF_FIELDS: &[&str] = &[
+    "name",
+    "symbol",
+    "uri",
+    "seller_fee_basis_points",
+    "creators",
+    "collection",
+    "update_authority",
+    "is_mutable",
+    "primary_sale_happened",
+];
+
+pub enum DiffBaseline {
+    /// A previous decode export in any of its output formats, picked by file extension:
+    /// a JSON map of mint to `JSONMetadata` or an array of them, NDJSON, or CSV. Mints the
+    /// export recorded as failed are reported as errors.
+    Export(PathBuf),
+    /// A directory of `<mint>.json` `NFTData` files or an `UpdateNFTData` array. Only the
+    /// fields `NFTData` holds are compared, and creators' `verified` flags are ignored since
+    /// updates can't set them.
+    NFTData(PathBuf),
+}
+
+pub struct DiffArgs<'a> {
+    pub client: &'a dyn AccountSource,
+    pub mint_list: Vec<String>,
+    pub baseline: DiffBaseline,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub expected: Value,
+    pub actual: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MintDiff {
+    pub mint: String,
+    pub changes: Vec<FieldChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MetadataExport {
+    ByMint(IndexMap<String, Value>),
+    List(Vec<Value>),
+}
+
+/// Baseline values by mint, or the reason a mint has no usable baseline.
+type Baseline = HashMap<String, Result<Value, String>>;
+
+/// Compares the current on-chain metadata of each mint against a baseline, field by field.
+pub async fn diff_metadata<'a>(args: &DiffArgs<'a>) -> AnyResult<Vec<MintDiff>> {
+    let baseline = load_baseline(&args.baseline)?;
+    let ignore_verified = matches!(args.baseline, DiffBaseline::NFTData(_));
+    let current = decode_metadata_batch(args.client, &args.mint_list, false).await;
+
+    let diffs = args
+        .mint_list
+        .iter()
+        .map(|mint| {
+            let error = |e: String| MintDiff {
+                mint: mint.clone(),
+                changes: Vec::new(),
+                error: Some(e),
+            };
+
+            let expected = match baseline.get(mint) {
+                Some(Ok(expected)) => expected,
+                Some(Err(e)) => return error(e.clone()),
+                None => return error("mint is missing from the baseline".to_string()),
+            };
+            let actual = match current.get(mint) {
+                Some(Ok(metadata)) => match serde_json::to_value(metadata) {
+                    Ok(actual) if ignore_verified => without_verified(actual),
+                    Ok(actual) => actual,
+                    Err(e) => return error(e.to_string()),
+                },
+                Some(Err(e)) => return error(e.to_string()),
+                None => return error("mint was not decoded".to_string()),
+            };
+
+            MintDiff {
+                mint: mint.clone(),
+                changes: diff_fields(expected, &actual),
+                error: None,
+            }
+        })
+        .collect();
+
+    Ok(diffs)
+}
+
+/// Lists the `DIFF_FIELDS` that differ, skipping fields the baseline doesn't have.
+pub fn diff_fields(expected: &Value, actual: &Value) -> Vec<FieldChange> {
+    DIFF_FIELDS
+        .iter()
+        .filter_map(|field| {
+            let expected = expected.get(field)?;
+            let actual = actual.get(field).unwrap_or(&Value::Null);
+
+            (expected != actual).then(|| FieldChange {
+                field: field.to_string(),
+                expected: expected.clone(),
+                actual: actual.clone(),
+            })
+        })
+        .collect()
+}
+
+fn load_baseline(baseline: &DiffBaseline) -> AnyResult<Baseline> {
+    let mut values = HashMap::new();
+
+    match baseline {
+        DiffBaseline::Export(path) => match OutputFormat::from_path(path) {
+            OutputFormat::Csv => {
+                let mut reader = csv::Reader::from_path(path)?;
+                for record in reader.deserialize::<DecodeCsvRecord>() {
+                    let record = record.map_err(|e| {
+                        anyhow!("Invalid metadata export {}: {}", path.display(), e)
+                    })?;
+                    values.insert(record.mint.clone(), csv_entry(record));
+                }
+            }
+            OutputFormat::Ndjson => {
+                for (i, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+                    let line = line?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let value: Value = serde_json::from_str(&line).map_err(|e| {
+                        anyhow!(
+                            "Invalid metadata export {} line {}: {}",
+                            path.display(),
+                            i + 1,
+                            e
+                        )
+                    })?;
+                    let mint = entry_mint(&value, path)?;
+                    values.insert(mint, export_entry(value));
+                }
+            }
+            OutputFormat::Pretty | OutputFormat::Compact => {
+                let export: MetadataExport = serde_json::from_reader(File::open(path)?)
+                    .map_err(|e| anyhow!("Invalid metadata export {}: {}", path.display(), e))?;
+
+                match export {
+                    MetadataExport::ByMint(entries) => {
+                        for (mint, value) in entries {
+                            values.insert(mint, export_entry(value));
+                        }
+                    }
+                    MetadataExport::List(entries) => {
+                        for value in entries {
+                            let mint = entry_mint(&value, path)?;
+                            values.insert(mint, export_entry(value));
+                        }
+                    }
+                }
+            }
+        },
+        DiffBaseline::NFTData(path) => {
+            for update in read_update_data(path)? {
+                let value = serde_json::to_value(&update.nft_data)?;
+                values.insert(update.mint_account, Ok(without_verified(value)));
+            }
+        }
+    }
+
+    Ok(values)
+}
+
+fn entry_mint(value: &Value, path: &Path) -> AnyResult<String> {
+    value
+        .get("mint")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| {
+            anyhow!(
+                "Metadata export {} has an entry without a mint",
+                path.display()
+            )
+        })
+}
+
+/// Reads a JSON or NDJSON export entry: decoded metadata, or the error the export recorded.
+fn export_entry(value: Value) -> Result<Value, String> {
+    if let Some(error) = value.get("error").and_then(Value::as_str) {
+        return Err(format!("baseline recorded an error: {}", error));
+    }
+
+    let metadata: JSONMetadata =
+        serde_json::from_value(value).map_err(|e| format!("invalid baseline entry: {}", e))?;
+    serde_json::to_value(&metadata).map_err(|e| e.to_string())
+}
+
+/// Rebuilds the compared fields of a CSV export row in their `JSONMetadata` shape.
+fn csv_entry(record: DecodeCsvRecord) -> Result<Value, String> {
+    if record.status != "ok" {
+        return Err(format!(
+            "baseline recorded an error: {}",
+            record.error.unwrap_or_default()
+        ));
+    }
+
+    let creators = match record.creators.as_deref() {
+        None | Some("") => Value::Null,
+        Some(creators) => creators
+            .split(';')
+            .map(|creator| match creator.split(':').collect::<Vec<_>>()[..] {
+                [address, share, verified] => Ok(json!({
+                    "address": address,
+                    "verified": verified.parse::<bool>().map_err(|e| e.to_string())?,
+                    "share": share.parse::<u8>().map_err(|e| e.to_string())?,
+                })),
+                _ => Err(format!("invalid baseline creator: {}", creator)),
+            })
+            .collect::<Result<Vec<_>, String>>()?
+            .into(),
+    };
+    let collection = match record.collection {
+        Some(key) => json!({
+            "verified": record.collection_verified.unwrap_or(false),
+            "key": key,
+        }),
+        None => Value::Null,
+    };
+
+    Ok(json!({
+        "name": record.name,
+        "symbol": record.symbol,
+        "uri": record.uri,
+        "seller_fee_basis_points": record.seller_fee_basis_points,
+        "creators": creators,
+        "collection": collection,
+        "update_authority": record.update_authority,
+        "is_mutable": record.is_mutable,
+        "primary_sale_happened": record.primary_sale_happened,
+    }))
+}
+
+/// Drops creators' `verified` flags, which only a creator's signature can change.
+fn without_verified(mut value: Value) -> Value {
+    if let Some(creators) = value.get_mut("creators").and_then(Value::as_array_mut) {
+        for creator in creators.iter_mut().filter_map(Value::as_object_mut) {
+            creator.remove("verified");
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_fields() {
+        let expected = json!({
+            "name": "Item #1",
+            "uri": "https://example.com/1.json",
+            "creators": [{ "address": "A", "verified": true, "share": 100 }],
+        });
+        let actual = json!({
+            "name": "Item #1",
+            "uri": "https://example.com/1.json",
+            "creators": [{ "address": "A", "verified": false, "share": 100 }],
+            "update_authority": "B",
+        });
+
+        let changes = diff_fields(&expected, &actual);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field, "creators");
+        assert_eq!(changes[0].actual[0]["verified"], json!(false));
+    }
+
+    #[test]
+    fn test_load_csv_baseline() {
+        let path = std::env::temp_dir().join(format!("diff-baseline-{}.csv", std::process::id()));
+        std::fs::write(
+            &path,
+            "mint,status,error,update_authority,name,symbol,uri,seller_fee_basis_points,creators,collection,collection_verified,primary_sale_happened,is_mutable,edition_nonce,token_standard,edition_type,edition,max_supply\n\
+             A,ok,,U,Item #1,ITM,https://example.com/1.json,500,C:100:true,,,false,true,,,,,\n\
+             B,error,no account data found for B,,,,,,,,,,,,,,,\n",
+        )
+        .unwrap();
+
+        let baseline = load_baseline(&DiffBaseline::Export(path.clone())).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let a = baseline["A"].as_ref().unwrap();
+        assert_eq!(a["name"], json!("Item #1"));
+        assert_eq!(
+            a["creators"],
+            json!([{ "address": "C", "verified": true, "share": 100 }])
+        );
+        assert_eq!(a["collection"], Value::Null);
+        assert!(baseline["B"]
+            .as_ref()
+            .unwrap_err()
+            .contains("no account data found"));
+    }
+
+    #[test]
+    fn test_without_verified() {
+        let expected = without_verified(json!({
+            "creators": [{ "address": "A", "share": 100 }],
+        }));
+        let actual = without_verified(json!({
+            "creators": [{ "address": "A", "verified": true, "share": 100 }],
+        }));
+
+        assert!(diff_fields(&expected, &actual).is_empty());
+    }
+}
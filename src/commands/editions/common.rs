@@ -0,0 +1,26 @@
+pub use anyhow::{anyhow, Result as AnyResult};
+pub use indexmap::IndexMap;
+pub use log::{debug, error, info};
+pub use mpl_token_metadata::state::{Edition, EditionMarker, Key, EDITION_MARKER_BIT_SIZE};
+pub use mpl_token_metadata::ID as TOKEN_METADATA_PROGRAM_ID;
+pub use serde::Serialize;
+pub use solana_account_decoder::UiAccountEncoding;
+pub use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+};
+pub use solana_program::borsh::try_from_slice_unchecked;
+pub use solana_sdk::{
+    account::Account,
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+};
+pub use crate::account_source::AccountSource;
+pub use crate::errors::ActionError;
+pub use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::Arc,
+};
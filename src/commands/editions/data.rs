@@ -0,0 +1,34 @@
+use super::common::*;
+
+#[derive(Debug, Serialize)]
+pub struct PrintEditions {
+    pub master_mint: String,
+    pub supply: u64,
+    pub max_supply: Option<u64>,
+    pub editions: Vec<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub print_mints: Option<IndexMap<u64, String>>,
+}
+
+pub struct ListEditionsArgs<'a> {
+    pub client: &'a dyn AccountSource,
+    pub master_mint: Pubkey,
+    pub resolve_mints: bool,
+}
+
+pub struct MintEditionsArgs<'a> {
+    pub client: &'a dyn AccountSource,
+    pub keypair: Arc<Keypair>,
+    pub master_mint: Pubkey,
+    pub receivers: Vec<Pubkey>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MintedEdition {
+    pub edition: u64,
+    pub mint: String,
+    pub receiver: String,
+    pub signature: String,
+}
+
+pub type MintEditionResults = Vec<Result<MintedEdition, ActionError>>;
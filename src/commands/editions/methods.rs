@@ -0,0 +1,265 @@
+use super::common::*;
+use super::data::*;
+
+use crate::commands::decode::{decode, decode_master_edition_from_mint};
+use crate::commands::snapshot::get_mint_accounts;
+use crate::constants::MAX_MULTIPLE_ACCOUNTS;
+use crate::derive::{derive_edition_marker_pda, derive_edition_pda};
+
+/// Lists the edition numbers printed from a master edition, optionally with their print mints.
+pub async fn list_editions<'a>(args: &ListEditionsArgs<'a>) -> AnyResult<PrintEditions> {
+    let master_edition =
+        decode_master_edition_from_mint(args.client, &args.master_mint.to_string()).await?;
+
+    let editions =
+        get_minted_editions(args.client, &args.master_mint, master_edition.supply).await?;
+    info!(
+        "Found {} minted editions for {}",
+        editions.len(),
+        args.master_mint
+    );
+
+    let print_mints = if args.resolve_mints {
+        Some(resolve_print_mints(args.client, &args.master_mint).await?)
+    } else {
+        None
+    };
+
+    Ok(PrintEditions {
+        master_mint: args.master_mint.to_string(),
+        supply: master_edition.supply,
+        max_supply: master_edition.max_supply,
+        editions,
+        print_mints,
+    })
+}
+
+/// Reads the edition marker ledgers of a master mint and returns every edition number in use.
+///
+/// Markers are walked from 0 up to `supply`, then onwards until the first missing marker, as
+/// burned prints lower the supply without freeing the higher edition numbers.
+///
+/// Edition markers don't store their master edition, so they can't be found with a program
+/// account scan. Editions beyond a missing marker past `supply`, e.g. printed by number far
+/// above the rest, are not found.
+pub async fn get_minted_editions(
+    client: &dyn AccountSource,
+    master_mint: &Pubkey,
+    supply: u64,
+) -> AnyResult<Vec<u64>> {
+    let mut editions = Vec::new();
+    let last_known_marker = supply / EDITION_MARKER_BIT_SIZE;
+    let mut marker_num = 0;
+
+    loop {
+        let marker_nums: Vec<u64> =
+            (marker_num..marker_num + MAX_MULTIPLE_ACCOUNTS as u64).collect();
+        let marker_pdas: Vec<Pubkey> = marker_nums
+            .iter()
+            .map(|n| derive_edition_marker_pda(master_mint, n * EDITION_MARKER_BIT_SIZE))
+            .collect();
+
+        let accounts = client.get_multiple_accounts(&marker_pdas).await?;
+
+        for (n, account) in marker_nums.iter().zip(accounts) {
+            match account {
+                Some(account) => {
+                    let marker = deserialize_edition_marker(&account.data).map_err(|e| {
+                        anyhow!("Invalid edition marker {} of {}: {}", n, master_mint, e)
+                    })?;
+                    editions.extend(ledger_editions(*n, &marker.ledger));
+                }
+                None if *n > last_known_marker => return Ok(editions),
+                None => debug!("Edition marker {} of {} not found", n, master_mint),
+            }
+        }
+
+        marker_num += MAX_MULTIPLE_ACCOUNTS as u64;
+    }
+}
+
+fn deserialize_edition_marker(data: &[u8]) -> std::io::Result<EditionMarker> {
+    match data.first() {
+        Some(k) if *k == Key::EditionMarker as u8 => try_from_slice_unchecked(data),
+        Some(k) => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "expected an edition marker account, found account key {}",
+                k
+            ),
+        )),
+        None => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "account data is empty",
+        )),
+    }
+}
+
+/// Returns the edition numbers set in the ledger of edition marker `marker_num`.
+pub fn ledger_editions(marker_num: u64, ledger: &[u8]) -> Vec<u64> {
+    let start = marker_num * EDITION_MARKER_BIT_SIZE;
+
+    ledger
+        .iter()
+        .enumerate()
+        .flat_map(|(i, byte)| {
+            (0..8u64)
+                .filter(move |bit| byte & (0b1000_0000 >> bit) != 0)
+                .map(move |bit| start + i as u64 * 8 + bit)
+        })
+        .collect()
+}
+
+/// Maps edition numbers to print mints.
+///
+/// Print edition accounts pointing at the master edition are found with a program account
+/// scan. Edition accounts don't store their mint, so the mints sharing the master's update
+/// authority are used as candidates and matched by their derived edition address.
+pub async fn resolve_print_mints(
+    client: &dyn AccountSource,
+    master_mint: &Pubkey,
+) -> AnyResult<IndexMap<u64, String>> {
+    let master_metadata = decode(client, master_mint).await?;
+    let master_edition_pda = derive_edition_pda(master_mint);
+
+    let edition_accounts = get_print_edition_accounts(client, &master_edition_pda).await?;
+    let edition_numbers: HashMap<Pubkey, u64> = edition_accounts
+        .into_iter()
+        .filter_map(|(pubkey, account)| {
+            let edition: Edition = try_from_slice_unchecked(&account.data).ok()?;
+            Some((pubkey, edition.edition))
+        })
+        .collect();
+    info!("Found {} print edition accounts", edition_numbers.len());
+
+    let candidate_mints = get_mint_accounts(
+        client,
+        &None,
+        0,
+        Some(master_metadata.update_authority.to_string()),
+        true,
+        false,
+    )
+    .await?;
+
+    let mut print_mints = IndexMap::new();
+
+    for mint in candidate_mints {
+        let mint_pubkey = match Pubkey::from_str(&mint) {
+            Ok(pubkey) => pubkey,
+            Err(_) => continue,
+        };
+
+        if let Some(edition) = edition_numbers.get(&derive_edition_pda(&mint_pubkey)) {
+            print_mints.insert(*edition, mint);
+        }
+    }
+
+    if print_mints.len() < edition_numbers.len() {
+        info!(
+            "Resolved {} of {} print mints, the rest have a different update authority",
+            print_mints.len(),
+            edition_numbers.len()
+        );
+    }
+
+    print_mints.sort_keys();
+    Ok(print_mints)
+}
+
+async fn get_print_edition_accounts(
+    client: &dyn AccountSource,
+    master_edition_pda: &Pubkey,
+) -> AnyResult<Vec<(Pubkey, Account)>> {
+    #[allow(deprecated)]
+    let key_filter = RpcFilterType::Memcmp(Memcmp {
+        offset: 0, // key
+        bytes: MemcmpEncodedBytes::Base58(bs58::encode([Key::EditionV1 as u8]).into_string()),
+        encoding: None,
+    });
+    #[allow(deprecated)]
+    let parent_filter = RpcFilterType::Memcmp(Memcmp {
+        offset: 1, // parent
+        bytes: MemcmpEncodedBytes::Base58(master_edition_pda.to_string()),
+        encoding: None,
+    });
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![key_filter, parent_filter]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: None,
+            commitment: Some(CommitmentConfig {
+                commitment: CommitmentLevel::Confirmed,
+            }),
+            min_context_slot: None,
+        },
+        with_context: None,
+    };
+
+    let accounts = client
+        .get_program_accounts_with_config(&TOKEN_METADATA_PROGRAM_ID, config)
+        .await?;
+
+    Ok(accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ledger_editions() {
+        let mut ledger = [0u8; 31];
+        // Editions 1 and 2 in the first byte, edition 9 in the second byte, 247 in the last.
+        ledger[0] = 0b0110_0000;
+        ledger[1] = 0b0100_0000;
+        ledger[30] = 0b0000_0001;
+
+        assert_eq!(ledger_editions(0, &ledger), vec![1, 2, 9, 247]);
+        assert_eq!(ledger_editions(2, &ledger), vec![497, 498, 505, 743]);
+    }
+
+    fn marker_account(key: Key, ledger: [u8; 31]) -> Account {
+        let mut data = vec![key as u8];
+        data.extend_from_slice(&ledger);
+        Account {
+            lamports: 1,
+            data,
+            owner: TOKEN_METADATA_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn test_get_minted_editions() {
+        use crate::account_source::MemoryAccountSource;
+        use futures::executor::block_on;
+
+        let master_mint = Pubkey::new_unique();
+        let mut ledger = [0u8; 31];
+        ledger[0] = 0b0110_0000;
+
+        let mut client = MemoryAccountSource::new();
+        for marker in [0, 2] {
+            client.insert(
+                derive_edition_marker_pda(&master_mint, marker * EDITION_MARKER_BIT_SIZE),
+                marker_account(Key::EditionMarker, ledger),
+            );
+        }
+
+        // Marker 1 is missing, so editions on marker 2 aren't found past a supply of 2.
+        let editions = block_on(get_minted_editions(&client, &master_mint, 2)).unwrap();
+        assert_eq!(editions, vec![1, 2]);
+
+        // A supply reaching marker 2 walks over the gap.
+        let editions = block_on(get_minted_editions(&client, &master_mint, 500)).unwrap();
+        assert_eq!(editions, vec![1, 2, 497, 498]);
+
+        client.insert(
+            derive_edition_marker_pda(&master_mint, 0),
+            marker_account(Key::MasterEditionV2, ledger),
+        );
+        assert!(block_on(get_minted_editions(&client, &master_mint, 2)).is_err());
+    }
+}
@@ -0,0 +1,7 @@
+mod common;
+mod data;
+mod methods;
+mod print;
+pub use data::*;
+pub use methods::*;
+pub use print::*;
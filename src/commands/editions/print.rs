@@ -0,0 +1,160 @@
+use super::common::*;
+use super::data::*;
+use super::methods::get_minted_editions;
+
+use crate::commands::decode::{decode, decode_master_edition_from_mint};
+use crate::derive::{derive_edition_pda, derive_metadata_pda};
+use crate::utils::{create_nft_mint_instructions, send_and_confirm_transaction_with_signers};
+use mpl_token_metadata::instruction::mint_new_edition_from_master_edition_via_token;
+use spl_associated_token_account::get_associated_token_address;
+
+/// Mints one print edition from `master_mint` to each receiver.
+///
+/// The keypair must hold the master edition token and pays for the new accounts. Edition
+/// numbers are picked from the first free bits of the edition marker ledgers, and the whole
+/// batch is rejected up front if it would exceed the master edition's `max_supply`.
+pub async fn mint_editions<'a>(args: &MintEditionsArgs<'a>) -> AnyResult<MintEditionResults> {
+    let master_edition =
+        decode_master_edition_from_mint(args.client, &args.master_mint.to_string()).await?;
+    let master_metadata = decode(args.client, &args.master_mint).await?;
+
+    let minted = get_minted_editions(args.client, &args.master_mint, master_edition.supply).await?;
+    let editions = next_free_editions(
+        &minted,
+        master_edition.supply,
+        master_edition.max_supply,
+        args.receivers.len(),
+    )?;
+
+    let master_token_account =
+        get_associated_token_address(&args.keypair.pubkey(), &args.master_mint);
+
+    let mut results = Vec::new();
+
+    for (edition, receiver) in editions.into_iter().zip(args.receivers.iter()) {
+        let new_mint = Keypair::new();
+
+        let result = mint_edition(
+            args,
+            &new_mint,
+            receiver,
+            edition,
+            &master_token_account,
+            &master_metadata.update_authority,
+        )
+        .await
+        .map(|signature| MintedEdition {
+            edition,
+            mint: new_mint.pubkey().to_string(),
+            receiver: receiver.to_string(),
+            signature,
+        })
+        .map_err(|e| ActionError::ActionFailed(new_mint.pubkey().to_string(), e.to_string()));
+
+        match result {
+            Ok(ref minted) => info!("Minted edition {} as {}", minted.edition, minted.mint),
+            Err(ref e) => error!("Failed to mint edition {}: {}", edition, e),
+        }
+
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
+async fn mint_edition<'a>(
+    args: &MintEditionsArgs<'a>,
+    new_mint: &Keypair,
+    receiver: &Pubkey,
+    edition: u64,
+    master_token_account: &Pubkey,
+    update_authority: &Pubkey,
+) -> AnyResult<String> {
+    let authority = args.keypair.pubkey();
+
+    let mut instructions = create_nft_mint_instructions(
+        args.client,
+        &authority,
+        &new_mint.pubkey(),
+        &authority,
+        receiver,
+    )
+    .await?;
+
+    instructions.push(mint_new_edition_from_master_edition_via_token(
+        TOKEN_METADATA_PROGRAM_ID,
+        derive_metadata_pda(&new_mint.pubkey()),
+        derive_edition_pda(&new_mint.pubkey()),
+        derive_edition_pda(&args.master_mint),
+        new_mint.pubkey(),
+        authority,
+        authority,
+        authority,
+        *master_token_account,
+        *update_authority,
+        derive_metadata_pda(&args.master_mint),
+        args.master_mint,
+        edition,
+    ));
+
+    let (signature, _) = send_and_confirm_transaction_with_signers(
+        args.client,
+        &[&*args.keypair, new_mint],
+        &instructions,
+    )
+    .await?;
+
+    Ok(signature)
+}
+
+/// Picks the lowest `count` edition numbers that are not taken yet.
+pub fn next_free_editions(
+    minted: &[u64],
+    supply: u64,
+    max_supply: Option<u64>,
+    count: usize,
+) -> AnyResult<Vec<u64>> {
+    if let Some(max_supply) = max_supply {
+        if supply + count as u64 > max_supply {
+            return Err(anyhow!(
+                "Minting {} editions would exceed the max supply of {} (current supply {})",
+                count,
+                max_supply,
+                supply
+            ));
+        }
+    }
+
+    let minted: HashSet<u64> = minted.iter().copied().collect();
+    let editions: Vec<u64> = (1..)
+        .filter(|edition| !minted.contains(edition))
+        .take_while(|edition| *edition <= max_supply.unwrap_or(u64::MAX))
+        .take(count)
+        .collect();
+
+    if editions.len() < count {
+        return Err(anyhow!(
+            "Only {} free edition numbers left below the max supply",
+            editions.len()
+        ));
+    }
+
+    Ok(editions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_free_editions() {
+        assert_eq!(
+            next_free_editions(&[1, 2, 4], 3, None, 3).unwrap(),
+            vec![3, 5, 6]
+        );
+        assert_eq!(next_free_editions(&[], 0, Some(2), 2).unwrap(), vec![1, 2]);
+        assert!(next_free_editions(&[1], 1, Some(2), 2).is_err());
+        // A burned print lowers the supply but its number is free again.
+        assert_eq!(next_free_editions(&[2], 1, Some(2), 1).unwrap(), vec![1]);
+    }
+}
@@ -0,0 +1,13 @@
+pub use anyhow::{anyhow, Result as AnyResult};
+pub use log::{error, info};
+pub use mpl_token_metadata::state::{Collection, Creator};
+pub use mpl_token_metadata::ID as TOKEN_METADATA_PROGRAM_ID;
+pub use serde::Serialize;
+pub use solana_sdk::{
+    instruction::Instruction, pubkey::Pubkey, signature::Keypair, signer::Signer,
+};
+pub use std::{fs, path::PathBuf, sync::Arc};
+
+pub use crate::account_source::AccountSource;
+pub use crate::data::NFTData;
+pub use crate::errors::ActionError;
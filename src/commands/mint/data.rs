@@ -0,0 +1,44 @@
+use super::common::*;
+
+pub struct MintNftArgs<'a> {
+    pub client: &'a dyn AccountSource,
+    pub keypair: Arc<Keypair>,
+    /// Defaults to the keypair.
+    pub receiver: Option<Pubkey>,
+    pub nft_data: NFTData,
+    pub immutable: bool,
+    pub primary_sale_happened: bool,
+    /// `None` allows unlimited prints, `Some(0)` makes a one of one.
+    pub max_editions: Option<u64>,
+    /// Collection to set and verify; the keypair must be its update authority.
+    pub collection_mint: Option<Pubkey>,
+    /// Verify the keypair in the creators array.
+    pub verify_creator: bool,
+    /// Makes the NFT a sized collection parent with this many verified items.
+    pub collection_size: Option<u64>,
+}
+
+pub struct MintDirectoryArgs<'a> {
+    pub client: &'a dyn AccountSource,
+    pub keypair: Arc<Keypair>,
+    pub receiver: Option<Pubkey>,
+    pub dir: PathBuf,
+    pub immutable: bool,
+    pub primary_sale_happened: bool,
+    pub max_editions: Option<u64>,
+    pub collection_mint: Option<Pubkey>,
+    pub verify_creator: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MintedNft {
+    pub mint: String,
+    pub metadata: String,
+    pub edition: String,
+    pub signature: String,
+    /// Signature of the collection verification, sent after the mint transaction.
+    pub collection_signature: Option<String>,
+}
+
+/// Results keyed by the `NFTData` file each NFT was minted from.
+pub type MintDirectoryResults = Vec<(String, Result<MintedNft, ActionError>)>;
@@ -0,0 +1,425 @@
+use super::common::*;
+use super::data::*;
+
+use crate::commands::decode::decode;
+use crate::derive::{derive_edition_pda, derive_metadata_pda};
+use crate::parse::convert_local_to_remote_data;
+use crate::utils::{
+    check_transaction_size, create_nft_mint_instructions, send_and_confirm_transaction_with_signers,
+};
+use crate::validate::validate_nft_data;
+use mpl_token_metadata::instruction::{
+    create_master_edition_v3, create_metadata_accounts_v3, set_collection_size, verify_collection,
+    verify_sized_collection_item,
+};
+
+/// Creates the mint, receiver token account, metadata and master edition of a new NFT in a
+/// single transaction, optionally verifying the keypair as creator.
+///
+/// The collection is verified in a second transaction, so long URIs with five creators still
+/// fit in the first one.
+pub async fn mint_nft<'a>(args: &MintNftArgs<'a>) -> AnyResult<MintedNft> {
+    let mint = Keypair::new();
+    let instructions = mint_nft_instructions(args, &mint.pubkey()).await?;
+    check_transaction_size(&instructions, &args.keypair.pubkey())?;
+
+    let verify_ix = match args.collection_mint {
+        Some(collection_mint) => {
+            Some(verify_collection_instruction(args, &mint.pubkey(), &collection_mint).await?)
+        }
+        None => None,
+    };
+
+    let (signature, _) = send_and_confirm_transaction_with_signers(
+        args.client,
+        &[&*args.keypair, &mint],
+        &instructions,
+    )
+    .await?;
+
+    info!("Minted {}", mint.pubkey());
+
+    let collection_signature = match verify_ix {
+        Some(ix) => {
+            let (signature, _) =
+                send_and_confirm_transaction_with_signers(args.client, &[&*args.keypair], &[ix])
+                    .await
+                    .map_err(|e| {
+                        anyhow!(
+                            "Minted {} but failed to verify its collection: {}",
+                            mint.pubkey(),
+                            e
+                        )
+                    })?;
+            Some(signature)
+        }
+        None => None,
+    };
+
+    Ok(MintedNft {
+        mint: mint.pubkey().to_string(),
+        metadata: derive_metadata_pda(&mint.pubkey()).to_string(),
+        edition: derive_edition_pda(&mint.pubkey()).to_string(),
+        signature,
+        collection_signature,
+    })
+}
+
+/// Mints one NFT per `NFTData` JSON file in `args.dir`, in file name order.
+pub async fn mint_directory<'a>(args: &MintDirectoryArgs<'a>) -> AnyResult<MintDirectoryResults> {
+    let mut results = Vec::new();
+
+    for (path, nft_data) in read_nft_data_dir(&args.dir)? {
+        let file = path.display().to_string();
+
+        let mint_args = MintNftArgs {
+            client: args.client,
+            keypair: args.keypair.clone(),
+            receiver: args.receiver,
+            nft_data,
+            immutable: args.immutable,
+            primary_sale_happened: args.primary_sale_happened,
+            max_editions: args.max_editions,
+            collection_mint: args.collection_mint,
+            verify_creator: args.verify_creator,
+            collection_size: None,
+        };
+
+        let result = mint_nft(&mint_args)
+            .await
+            .map_err(|e| ActionError::ActionFailed(file.clone(), e.to_string()));
+
+        if let Err(ref e) = result {
+            error!("{}", e);
+        }
+
+        results.push((file, result));
+    }
+
+    Ok(results)
+}
+
+/// Reads and parses every `.json` file in `dir`, sorted by path.
+pub fn read_nft_data_dir(dir: &PathBuf) -> AnyResult<Vec<(PathBuf, NFTData)>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let file = fs::File::open(&path)?;
+            let nft_data: NFTData = serde_json::from_reader(file)
+                .map_err(|e| anyhow!("Invalid NFT data in {}: {}", path.display(), e))?;
+            Ok((path, nft_data))
+        })
+        .collect()
+}
+
+async fn mint_nft_instructions<'a>(
+    args: &MintNftArgs<'a>,
+    mint: &Pubkey,
+) -> AnyResult<Vec<Instruction>> {
+    let authority = args.keypair.pubkey();
+    let receiver = args.receiver.unwrap_or(authority);
+    let metadata = derive_metadata_pda(mint);
+
+    validate_nft_data(&args.nft_data, None).check()?;
+    let data = convert_local_to_remote_data(args.nft_data.clone())?;
+    let creators = data
+        .creators
+        .map(|c| set_creator_verification(c, &authority, args.verify_creator))
+        .transpose()?;
+
+    let mut instructions =
+        create_nft_mint_instructions(args.client, &authority, mint, &authority, &receiver).await?;
+
+    instructions.push(create_metadata_accounts_v3(
+        TOKEN_METADATA_PROGRAM_ID,
+        metadata,
+        *mint,
+        authority,
+        authority,
+        authority,
+        data.name,
+        data.symbol,
+        data.uri,
+        creators,
+        data.seller_fee_basis_points,
+        true,
+        !args.immutable,
+        args.collection_mint.map(|key| Collection {
+            verified: false,
+            key,
+        }),
+        None,
+        None,
+    ));
+
+    instructions.push(create_master_edition_v3(
+        TOKEN_METADATA_PROGRAM_ID,
+        derive_edition_pda(mint),
+        *mint,
+        authority,
+        authority,
+        metadata,
+        authority,
+        args.max_editions,
+    ));
+
+    // The program always creates sized collections with a size of 0, so an unsized parent is
+    // created and sized in the same transaction instead.
+    if let Some(size) = args.collection_size {
+        instructions.push(set_collection_size(
+            TOKEN_METADATA_PROGRAM_ID,
+            metadata,
+            authority,
+            *mint,
+            None,
+            size,
+        ));
+    }
+
+    Ok(instructions)
+}
+
+async fn verify_collection_instruction<'a>(
+    args: &MintNftArgs<'a>,
+    mint: &Pubkey,
+    collection_mint: &Pubkey,
+) -> AnyResult<Instruction> {
+    let authority = args.keypair.pubkey();
+    let collection_metadata = decode(args.client, collection_mint).await?;
+
+    let verify = if collection_metadata.collection_details.is_some() {
+        verify_sized_collection_item
+    } else {
+        verify_collection
+    };
+
+    Ok(verify(
+        TOKEN_METADATA_PROGRAM_ID,
+        derive_metadata_pda(mint),
+        authority,
+        authority,
+        *collection_mint,
+        derive_metadata_pda(collection_mint),
+        derive_edition_pda(collection_mint),
+        None,
+    ))
+}
+
+/// Only the update authority can be verified at creation time, so all other flags are cleared.
+pub fn set_creator_verification(
+    creators: Vec<Creator>,
+    authority: &Pubkey,
+    verify_creator: bool,
+) -> AnyResult<Vec<Creator>> {
+    if verify_creator && !creators.iter().any(|c| c.address == *authority) {
+        return Err(anyhow!("{} is not in the creators array", authority));
+    }
+
+    Ok(creators
+        .into_iter()
+        .map(|c| Creator {
+            verified: verify_creator && c.address == *authority,
+            ..c
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_creator_verification() {
+        let authority = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let creators = vec![
+            Creator {
+                address: other,
+                verified: true,
+                share: 50,
+            },
+            Creator {
+                address: authority,
+                verified: false,
+                share: 50,
+            },
+        ];
+
+        let verified = set_creator_verification(creators.clone(), &authority, true).unwrap();
+        assert!(!verified[0].verified);
+        assert!(verified[1].verified);
+
+        let unverified = set_creator_verification(creators.clone(), &authority, false).unwrap();
+        assert!(unverified.iter().all(|c| !c.verified));
+
+        assert!(set_creator_verification(creators, &Pubkey::new_unique(), true).is_err());
+    }
+
+    #[test]
+    fn test_mint_transaction_size() {
+        let authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let metadata = derive_metadata_pda(&mint);
+        let creators = (0..5)
+            .map(|_| Creator {
+                address: Pubkey::new_unique(),
+                verified: false,
+                share: 20,
+            })
+            .collect();
+
+        let mut instructions = vec![
+            create_metadata_accounts_v3(
+                TOKEN_METADATA_PROGRAM_ID,
+                metadata,
+                mint,
+                authority,
+                authority,
+                authority,
+                "n".repeat(32),
+                "s".repeat(10),
+                "u".repeat(200),
+                Some(creators),
+                500,
+                true,
+                true,
+                Some(Collection {
+                    verified: false,
+                    key: Pubkey::new_unique(),
+                }),
+                None,
+                None,
+            ),
+            create_master_edition_v3(
+                TOKEN_METADATA_PROGRAM_ID,
+                derive_edition_pda(&mint),
+                mint,
+                authority,
+                authority,
+                metadata,
+                authority,
+                Some(0),
+            ),
+        ];
+        assert!(check_transaction_size(&instructions, &authority).is_ok());
+
+        instructions.push(instructions[0].clone());
+        instructions.push(instructions[0].clone());
+        assert!(check_transaction_size(&instructions, &authority).is_err());
+    }
+
+    #[test]
+    fn test_mint_nft_transactions() {
+        use crate::account_source::fixtures;
+        use crate::data::NFTCreator;
+        use futures::executor::block_on;
+        use solana_sdk::{hash::Hash, message::Message};
+
+        let mut source = fixtures::fixture_source();
+        let blockhash = Hash::new_unique();
+        source.set_blockhash(blockhash);
+
+        let keypair = Arc::new(fixtures::update_authority());
+        let authority = keypair.pubkey();
+        let args = MintNftArgs {
+            client: &source,
+            keypair,
+            receiver: None,
+            nft_data: NFTData {
+                name: "Test".to_string(),
+                symbol: "TST".to_string(),
+                uri: "https://example.com/test.json".to_string(),
+                seller_fee_basis_points: 500,
+                creators: Some(vec![NFTCreator {
+                    address: authority.to_string(),
+                    verified: false,
+                    share: 100,
+                }]),
+            },
+            immutable: false,
+            primary_sale_happened: false,
+            max_editions: Some(0),
+            collection_mint: Some(fixtures::MINT),
+            verify_creator: true,
+            collection_size: None,
+        };
+
+        let minted = block_on(mint_nft(&args)).unwrap();
+        let mint: Pubkey = minted.mint.parse().unwrap();
+        assert_eq!(minted.metadata, derive_metadata_pda(&mint).to_string());
+        assert_eq!(minted.edition, derive_edition_pda(&mint).to_string());
+
+        let mut instructions = block_on(create_nft_mint_instructions(
+            &source, &authority, &mint, &authority, &authority,
+        ))
+        .unwrap();
+        instructions.push(create_metadata_accounts_v3(
+            TOKEN_METADATA_PROGRAM_ID,
+            derive_metadata_pda(&mint),
+            mint,
+            authority,
+            authority,
+            authority,
+            "Test".to_string(),
+            "TST".to_string(),
+            "https://example.com/test.json".to_string(),
+            Some(vec![Creator {
+                address: authority,
+                verified: true,
+                share: 100,
+            }]),
+            500,
+            true,
+            true,
+            Some(Collection {
+                verified: false,
+                key: fixtures::MINT,
+            }),
+            None,
+            None,
+        ));
+        instructions.push(create_master_edition_v3(
+            TOKEN_METADATA_PROGRAM_ID,
+            derive_edition_pda(&mint),
+            mint,
+            authority,
+            authority,
+            derive_metadata_pda(&mint),
+            authority,
+            Some(0),
+        ));
+        let verify_ix = verify_collection(
+            TOKEN_METADATA_PROGRAM_ID,
+            derive_metadata_pda(&mint),
+            authority,
+            authority,
+            fixtures::MINT,
+            derive_metadata_pda(&fixtures::MINT),
+            derive_edition_pda(&fixtures::MINT),
+            None,
+        );
+
+        let sent = source.sent_transactions();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(
+            sent[0].message,
+            Message::new_with_blockhash(&instructions, Some(&authority), &blockhash)
+        );
+        assert_eq!(sent[0].signatures.len(), 2);
+        assert_eq!(minted.signature, sent[0].signatures[0].to_string());
+        assert_eq!(
+            sent[1].message,
+            Message::new_with_blockhash(&[verify_ix], Some(&authority), &blockhash)
+        );
+        assert_eq!(
+            minted.collection_signature,
+            Some(sent[1].signatures[0].to_string())
+        );
+    }
+}
@@ -1,6 +1,10 @@
 pub mod burn;
 pub mod collections;
 pub mod decode;
+pub mod diff;
+pub mod editions;
+pub mod mint;
+pub mod sign;
 pub mod snapshot;
 pub mod theindexio;
 pub mod update;
@@ -0,0 +1,11 @@
+pub use anyhow::Result as AnyResult;
+pub use futures::stream::{self, StreamExt};
+pub use log::{error, info};
+pub use mpl_token_metadata::state::Creator;
+pub use mpl_token_metadata::ID as TOKEN_METADATA_PROGRAM_ID;
+pub use serde::Serialize;
+pub use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+pub use std::sync::Arc;
+
+pub use crate::account_source::AccountSource;
+pub use crate::errors::ActionError;
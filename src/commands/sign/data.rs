@@ -0,0 +1,16 @@
+use super::common::*;
+
+pub struct SignAllArgs<'a> {
+    pub client: &'a dyn AccountSource,
+    /// The creator signing, or removing its verification from, every matching mint.
+    pub keypair: Arc<Keypair>,
+    pub batch_size: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignedMint {
+    pub mint: String,
+    pub signature: String,
+}
+
+pub type SignResults = Vec<Result<SignedMint, ActionError>>;
@@ -0,0 +1,147 @@
+use super::common::*;
+use super::data::*;
+
+use crate::commands::decode::deserialize_metadata;
+use crate::commands::snapshot::get_cm_creator_accounts;
+use crate::derive::derive_metadata_pda;
+use crate::utils::async_send_and_confirm_transaction;
+use mpl_token_metadata::instruction::{remove_creator_verification, sign_metadata};
+use mpl_token_metadata::state::MAX_CREATOR_LIMIT;
+use std::collections::HashSet;
+
+/// Verifies the keypair as a creator of `mint`.
+pub async fn sign_mint(
+    client: &dyn AccountSource,
+    keypair: Arc<Keypair>,
+    mint: &Pubkey,
+) -> AnyResult<String> {
+    set_verification(client, keypair, mint, true).await
+}
+
+/// Removes the keypair's creator verification from `mint`.
+pub async fn unverify_mint(
+    client: &dyn AccountSource,
+    keypair: Arc<Keypair>,
+    mint: &Pubkey,
+) -> AnyResult<String> {
+    set_verification(client, keypair, mint, false).await
+}
+
+/// Signs every mint where the keypair is an unverified creator.
+pub async fn sign_all<'a>(args: &SignAllArgs<'a>) -> AnyResult<SignResults> {
+    let mints = get_creator_mints(args.client, &args.keypair.pubkey(), false).await?;
+    info!("Signing {} mints", mints.len());
+
+    Ok(set_verification_all(args, mints, true).await)
+}
+
+/// Removes the keypair's verification from every mint where it is a verified creator.
+pub async fn unverify_all<'a>(args: &SignAllArgs<'a>) -> AnyResult<SignResults> {
+    let mints = get_creator_mints(args.client, &args.keypair.pubkey(), true).await?;
+    info!("Unverifying {} mints", mints.len());
+
+    Ok(set_verification_all(args, mints, false).await)
+}
+
+async fn set_verification(
+    client: &dyn AccountSource,
+    keypair: Arc<Keypair>,
+    mint: &Pubkey,
+    verify: bool,
+) -> AnyResult<String> {
+    let metadata = derive_metadata_pda(mint);
+
+    let ix = if verify {
+        sign_metadata(TOKEN_METADATA_PROGRAM_ID, metadata, keypair.pubkey())
+    } else {
+        remove_creator_verification(TOKEN_METADATA_PROGRAM_ID, metadata, keypair.pubkey())
+    };
+    let (signature, _) = async_send_and_confirm_transaction(client, keypair, &[ix]).await?;
+
+    Ok(signature)
+}
+
+async fn set_verification_all<'a>(
+    args: &SignAllArgs<'a>,
+    mints: Vec<Pubkey>,
+    verify: bool,
+) -> SignResults {
+    stream::iter(mints)
+        .map(|mint| async move {
+            set_verification(args.client, args.keypair.clone(), &mint, verify)
+                .await
+                .map(|signature| SignedMint {
+                    mint: mint.to_string(),
+                    signature,
+                })
+                .map_err(|e| {
+                    error!("{}: {}", mint, e);
+                    ActionError::ActionFailed(mint.to_string(), e.to_string())
+                })
+        })
+        .buffer_unordered(args.batch_size.max(1))
+        .collect()
+        .await
+}
+
+/// Finds the mints listing `creator` in any creator position with the given verified flag.
+pub async fn get_creator_mints(
+    client: &dyn AccountSource,
+    creator: &Pubkey,
+    verified: bool,
+) -> AnyResult<Vec<Pubkey>> {
+    let mut mints = Vec::new();
+    let mut seen = HashSet::new();
+
+    for position in 0..MAX_CREATOR_LIMIT {
+        let accounts = get_cm_creator_accounts(client, &creator.to_string(), position).await?;
+
+        for (_, account) in accounts {
+            let metadata = match deserialize_metadata(&account.data) {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+
+            if has_creator(metadata.data.creators.as_deref(), creator, verified)
+                && seen.insert(metadata.mint)
+            {
+                mints.push(metadata.mint);
+            }
+        }
+    }
+
+    Ok(mints)
+}
+
+pub fn has_creator(creators: Option<&[Creator]>, creator: &Pubkey, verified: bool) -> bool {
+    creators
+        .unwrap_or_default()
+        .iter()
+        .any(|c| c.address == *creator && c.verified == verified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_creator() {
+        let creator = Pubkey::new_unique();
+        let creators = vec![
+            Creator {
+                address: Pubkey::new_unique(),
+                verified: true,
+                share: 50,
+            },
+            Creator {
+                address: creator,
+                verified: false,
+                share: 50,
+            },
+        ];
+
+        assert!(has_creator(Some(&creators), &creator, false));
+        assert!(!has_creator(Some(&creators), &creator, true));
+        assert!(!has_creator(None, &creator, false));
+    }
+}
@@ -0,0 +1,6 @@
+mod common;
+mod data;
+mod methods;
+
+pub use data::*;
+pub use methods::*;
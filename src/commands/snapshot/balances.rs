@@ -0,0 +1,205 @@
+use futures::stream::{self, StreamExt, TryStreamExt};
+use indexmap::IndexMap;
+use solana_program::program_pack::Pack;
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+
+use super::common::*;
+use super::data::*;
+use super::methods::{get_holder_token_accounts, get_metadata_accounts};
+
+use crate::commands::decode::deserialize_metadata;
+use crate::constants::MAX_MULTIPLE_ACCOUNTS;
+use crate::parse::creator_is_verified;
+
+/// Snapshots every non-zero token account of the matching mints, whatever their supply,
+/// and aggregates them per owner and mint. Fails if any mint or its token accounts can't be
+/// fetched, rather than returning a snapshot with that mint's holders missing or its amounts
+/// scaled wrongly.
+pub async fn snapshot_balances(
+    client: &dyn AccountSource,
+    args: SnapshotBalancesArgs,
+) -> Result<BalanceSnapshot> {
+    let accounts = get_metadata_accounts(
+        client,
+        &args.creator,
+        args.position,
+        &args.update_authority,
+        args.v2,
+    )
+    .await?;
+
+    let mut mints = Vec::new();
+    for (metadata_pubkey, account) in accounts {
+        let metadata = match deserialize_metadata(&account.data) {
+            Ok(metadata) => metadata,
+            Err(_) => {
+                error!("Failed to parse metadata for account {}", metadata_pubkey);
+                continue;
+            }
+        };
+
+        if creator_is_verified(&metadata.data.creators, args.position) || args.allow_unverified {
+            mints.push(metadata.mint);
+        }
+    }
+    mints.sort_unstable();
+
+    let decimals = get_mint_decimals(client, &mints).await?;
+
+    info!("Finding token accounts of {} mints...", mints.len());
+    let results: Vec<Vec<TokenBalance>> = stream::iter(mints.iter().zip(decimals))
+        .map(|(mint, decimals)| get_mint_balances(client, mint, decimals))
+        .buffered(PARALLEL_LIMIT)
+        .try_collect()
+        .await?;
+
+    let balances: Vec<TokenBalance> = results.into_iter().flatten().collect();
+    let owners = aggregate_balances(&balances);
+
+    Ok(BalanceSnapshot { balances, owners })
+}
+
+/// Sums balances per owner and mint, keeping the order owners are first seen in.
+pub fn aggregate_balances(balances: &[TokenBalance]) -> Vec<OwnerBalance> {
+    let mut owners: IndexMap<(&str, &str), OwnerBalance> = IndexMap::new();
+
+    for balance in balances {
+        let owner = owners
+            .entry((&balance.owner_wallet, &balance.mint_account))
+            .or_insert_with(|| OwnerBalance {
+                owner_wallet: balance.owner_wallet.clone(),
+                mint_account: balance.mint_account.clone(),
+                amount: 0,
+                decimals: balance.decimals,
+                token_accounts: 0,
+                frozen: false,
+                delegated: false,
+            });
+
+        owner.amount += balance.amount;
+        owner.token_accounts += 1;
+        owner.frozen |= balance.frozen;
+        owner.delegated |= balance.delegate.is_some();
+    }
+
+    owners.into_values().collect()
+}
+
+async fn get_mint_decimals(client: &dyn AccountSource, mints: &[Pubkey]) -> Result<Vec<u8>> {
+    let mut decimals = Vec::with_capacity(mints.len());
+
+    for chunk in mints.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = client.get_multiple_accounts(chunk).await?;
+
+        for (mint, account) in chunk.iter().zip(accounts) {
+            let account = account.ok_or_else(|| anyhow!("Mint account {} not found", mint))?;
+            let mint_account = Mint::unpack(&account.data)
+                .map_err(|err| anyhow!("Invalid mint account {}: {}", mint, err))?;
+            decimals.push(mint_account.decimals);
+        }
+    }
+
+    Ok(decimals)
+}
+
+async fn get_mint_balances(
+    client: &dyn AccountSource,
+    mint: &Pubkey,
+    decimals: u8,
+) -> Result<Vec<TokenBalance>> {
+    let token_accounts = get_holder_token_accounts(client, mint.to_string())
+        .await
+        .map_err(|err| anyhow!("Failed to get token accounts of {}: {}", mint, err))?;
+
+    let mut balances: Vec<TokenBalance> = token_accounts
+        .into_iter()
+        .filter_map(|(token_account, account)| {
+            let token = match TokenAccount::unpack(&account.data) {
+                Ok(token) => token,
+                Err(err) => {
+                    error!("Account {} has no data: {}", token_account, err);
+                    return None;
+                }
+            };
+
+            (token.amount > 0).then(|| TokenBalance {
+                owner_wallet: token.owner.to_string(),
+                mint_account: mint.to_string(),
+                token_account: token_account.to_string(),
+                amount: token.amount,
+                decimals,
+                frozen: token.state == AccountState::Frozen,
+                delegate: Option::<Pubkey>::from(token.delegate).map(|d| d.to_string()),
+            })
+        })
+        .collect();
+    balances.sort_unstable_by(|a, b| a.owner_wallet.cmp(&b.owner_wallet));
+
+    Ok(balances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance(owner: &str, amount: u64, frozen: bool, delegate: Option<&str>) -> TokenBalance {
+        TokenBalance {
+            owner_wallet: owner.to_string(),
+            mint_account: "mint".to_string(),
+            token_account: format!("{}-{}", owner, amount),
+            amount,
+            decimals: 0,
+            frozen,
+            delegate: delegate.map(|d| d.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_aggregate_balances() {
+        let balances = vec![
+            balance("alice", 5, false, None),
+            balance("bob", 1, true, None),
+            balance("alice", 3, false, Some("market")),
+        ];
+
+        let owners = aggregate_balances(&balances);
+        assert_eq!(owners.len(), 2);
+        assert_eq!(owners[0].owner_wallet, "alice");
+        assert_eq!(owners[0].amount, 8);
+        assert_eq!(owners[0].token_accounts, 2);
+        assert!(owners[0].delegated && !owners[0].frozen);
+        assert!(owners[1].frozen && !owners[1].delegated);
+    }
+
+    #[test]
+    fn test_get_mint_decimals() {
+        let mint = Pubkey::new_unique();
+        let mut data = vec![0; Mint::LEN];
+        Mint::pack(
+            Mint {
+                decimals: 6,
+                is_initialized: true,
+                ..Default::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+
+        let mut client = crate::account_source::MemoryAccountSource::new();
+        client.insert(
+            mint,
+            Account {
+                lamports: 1,
+                data,
+                owner: TOKEN_PROGRAM_ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+
+        let decimals =
+            |mints: &[Pubkey]| futures::executor::block_on(get_mint_decimals(&client, mints));
+        assert_eq!(decimals(&[mint]).unwrap(), vec![6]);
+        assert!(decimals(&[mint, Pubkey::new_unique()]).is_err());
+    }
+}
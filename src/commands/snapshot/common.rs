@@ -1,6 +1,6 @@
 pub use anyhow::{anyhow, Result};
-pub use log::{error, info};
-pub use mpl_token_metadata::state::Metadata;
+pub use crate::account_source::AccountSource;
+pub use log::{debug, error, info};
 pub use mpl_token_metadata::ID as TOKEN_METADATA_PROGRAM_ID;
 pub use serde::Serialize;
 pub use solana_account_decoder::{
@@ -8,11 +8,9 @@ pub use solana_account_decoder::{
     UiAccountEncoding,
 };
 pub use solana_client::{
-    nonblocking::rpc_client::RpcClient,
     rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
     rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
 };
-pub use solana_program::borsh::try_from_slice_unchecked;
 pub use solana_sdk::{
     account::Account,
     commitment_config::{CommitmentConfig, CommitmentLevel},
@@ -20,6 +18,7 @@ pub use solana_sdk::{
 };
 pub use spl_token::ID as TOKEN_PROGRAM_ID;
 pub use std::{
+    collections::HashSet,
     str::FromStr,
     sync::{Arc, Mutex},
 };
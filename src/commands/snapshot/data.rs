@@ -1,4 +1,6 @@
 use super::common::*;
+use crate::commands::decode::{JSONCollection, JSONCreator, JSONEdition};
+use crate::output::OutputFormat;
 
 pub const PARALLEL_LIMIT: usize = 50;
 pub type HolderResults = Vec<Result<Holder>>;
@@ -35,6 +37,8 @@ pub struct SnapshotMintsArgs {
     pub update_authority: Option<String>,
     pub v2: bool,
     pub allow_unverified: bool,
+    /// File the results are written to, in the format its extension names, or a directory to
+    /// write `<creator or update authority>_<kind>.json` into. Empty to skip.
     pub output: String,
 }
 
@@ -45,5 +49,175 @@ pub struct SnapshotHoldersArgs {
     pub mint_accounts_file: Option<String>,
     pub v2: bool,
     pub allow_unverified: bool,
+    /// File the results are written to, in the format its extension names, or a directory to
+    /// write `<creator or update authority>_<kind>.json` into. Empty to skip.
     pub output: String,
 }
+
+pub struct SnapshotMetadataArgs {
+    pub creator: Option<String>,
+    pub position: usize,
+    pub update_authority: Option<String>,
+    pub v2: bool,
+    pub allow_unverified: bool,
+}
+
+/// Everything known about one mint of a collection.
+#[derive(Debug, Serialize)]
+pub struct MetadataRecord {
+    pub mint: String,
+    pub metadata_account: String,
+    pub update_authority: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<JSONCreator>,
+    pub collection: Option<JSONCollection>,
+    pub token_standard: Option<String>,
+    pub edition: Option<JSONEdition>,
+    pub supply: Option<u64>,
+    /// Owner of the largest token account.
+    pub holder: Option<String>,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+}
+
+/// `MetadataRecord` flattened into CSV columns.
+#[derive(Debug, Serialize)]
+pub struct MetadataCsvRecord {
+    pub mint: String,
+    pub metadata_account: String,
+    pub update_authority: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    /// `address:share:verified` entries separated by `;`.
+    pub creators: String,
+    pub collection: Option<String>,
+    pub collection_verified: Option<bool>,
+    pub token_standard: Option<String>,
+    pub edition_type: Option<String>,
+    pub edition: Option<u64>,
+    pub max_supply: Option<u64>,
+    pub supply: Option<u64>,
+    pub holder: Option<String>,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+}
+
+impl From<&MetadataRecord> for MetadataCsvRecord {
+    fn from(record: &MetadataRecord) -> Self {
+        let creators: Vec<String> = record
+            .creators
+            .iter()
+            .map(|c| format!("{}:{}:{}", c.address, c.share, c.verified))
+            .collect();
+
+        let (edition_type, edition, max_supply) = match record.edition {
+            Some(JSONEdition::Master { supply, max_supply }) => {
+                (Some("master".to_string()), Some(supply), max_supply)
+            }
+            Some(JSONEdition::Print { edition, .. }) => {
+                (Some("print".to_string()), Some(edition), None)
+            }
+            None => (None, None, None),
+        };
+
+        Self {
+            mint: record.mint.clone(),
+            metadata_account: record.metadata_account.clone(),
+            update_authority: record.update_authority.clone(),
+            name: record.name.clone(),
+            symbol: record.symbol.clone(),
+            uri: record.uri.clone(),
+            seller_fee_basis_points: record.seller_fee_basis_points,
+            creators: creators.join(";"),
+            collection: record.collection.as_ref().map(|c| c.key.clone()),
+            collection_verified: record.collection.as_ref().map(|c| c.verified),
+            token_standard: record.token_standard.clone(),
+            edition_type,
+            edition,
+            max_supply,
+            supply: record.supply,
+            holder: record.holder.clone(),
+            primary_sale_happened: record.primary_sale_happened,
+            is_mutable: record.is_mutable,
+        }
+    }
+}
+
+pub struct StreamSnapshotArgs {
+    pub creator: Option<String>,
+    pub position: usize,
+    pub update_authority: Option<String>,
+    pub v2: bool,
+    pub allow_unverified: bool,
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StreamedMint {
+    pub mint_account: String,
+    pub metadata_account: String,
+}
+
+pub struct SnapshotBalancesArgs {
+    pub creator: Option<String>,
+    pub position: usize,
+    pub update_authority: Option<String>,
+    pub v2: bool,
+    pub allow_unverified: bool,
+}
+
+/// A non-zero token account of a snapshotted mint.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TokenBalance {
+    pub owner_wallet: String,
+    pub mint_account: String,
+    pub token_account: String,
+    pub amount: u64,
+    pub decimals: u8,
+    pub frozen: bool,
+    pub delegate: Option<String>,
+}
+
+/// All token accounts of one owner for one mint.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct OwnerBalance {
+    pub owner_wallet: String,
+    pub mint_account: String,
+    pub amount: u64,
+    pub decimals: u8,
+    pub token_accounts: usize,
+    /// At least one of the owner's accounts is frozen.
+    pub frozen: bool,
+    /// At least one of the owner's accounts has a delegate.
+    pub delegated: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BalanceSnapshot {
+    pub balances: Vec<TokenBalance>,
+    pub owners: Vec<OwnerBalance>,
+}
+
+#[derive(Debug, Default)]
+pub struct LeaderboardOptions {
+    /// Wallets left out of the leaderboard, e.g. marketplace escrows.
+    pub exclude: HashSet<String>,
+    /// Leave out owners that are PDAs, which are usually program escrows rather than people.
+    pub exclude_off_curve: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WalletHoldings {
+    /// Wallets holding the same count share a rank.
+    pub rank: usize,
+    pub owner_wallet: String,
+    pub count: usize,
+    /// Share of all snapshotted mints, excluded wallets included.
+    pub percentage: f64,
+    pub mints: Vec<String>,
+}
@@ -0,0 +1,135 @@
+use indexmap::IndexMap;
+use std::{fs::File, path::Path};
+
+use super::common::*;
+use super::data::*;
+
+/// Groups holders by wallet, sorted by the number of mints held, then by wallet.
+pub fn holder_leaderboard(holders: &[Holder], options: &LeaderboardOptions) -> Vec<WalletHoldings> {
+    let total_mints = holders
+        .iter()
+        .map(|h| h.mint_account.as_str())
+        .collect::<HashSet<_>>()
+        .len();
+
+    let mut wallets: IndexMap<&str, Vec<String>> = IndexMap::new();
+    for holder in holders {
+        if is_excluded(&holder.owner_wallet, options) {
+            continue;
+        }
+        wallets
+            .entry(&holder.owner_wallet)
+            .or_default()
+            .push(holder.mint_account.clone());
+    }
+
+    let mut leaderboard: Vec<WalletHoldings> = wallets
+        .into_iter()
+        .map(|(owner_wallet, mut mints)| {
+            mints.sort_unstable();
+            WalletHoldings {
+                rank: 0,
+                owner_wallet: owner_wallet.to_string(),
+                count: mints.len(),
+                percentage: mints.len() as f64 * 100.0 / total_mints as f64,
+                mints,
+            }
+        })
+        .collect();
+
+    leaderboard.sort_unstable_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.owner_wallet.cmp(&b.owner_wallet))
+    });
+
+    for i in 0..leaderboard.len() {
+        leaderboard[i].rank = if i > 0 && leaderboard[i].count == leaderboard[i - 1].count {
+            leaderboard[i - 1].rank
+        } else {
+            i + 1
+        };
+    }
+
+    leaderboard
+}
+
+/// Reads a JSON array of wallet addresses to exclude.
+pub fn load_exclusion_list<P: AsRef<Path>>(path: P) -> Result<HashSet<String>> {
+    let file = File::open(path.as_ref())?;
+    let wallets: Vec<String> = serde_json::from_reader(file).map_err(|e| {
+        anyhow!(
+            "{} is not a JSON array of addresses: {}",
+            path.as_ref().display(),
+            e
+        )
+    })?;
+
+    for wallet in &wallets {
+        Pubkey::from_str(wallet).map_err(|_| anyhow!("Invalid wallet address: {}", wallet))?;
+    }
+
+    Ok(wallets.into_iter().collect())
+}
+
+fn is_excluded(owner_wallet: &str, options: &LeaderboardOptions) -> bool {
+    if options.exclude.contains(owner_wallet) {
+        return true;
+    }
+
+    options.exclude_off_curve
+        && Pubkey::from_str(owner_wallet)
+            .map(|owner| !owner.is_on_curve())
+            .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{signature::Keypair, signer::Signer};
+
+    fn holder(owner: &str, mint: &str) -> Holder {
+        Holder {
+            owner_wallet: owner.to_string(),
+            mint_account: mint.to_string(),
+            metadata_account: String::new(),
+            associated_token_address: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_holder_leaderboard() {
+        let alice = Keypair::new().pubkey().to_string();
+        let bob = Keypair::new().pubkey().to_string();
+        let carol = Keypair::new().pubkey().to_string();
+        let escrow = Pubkey::find_program_address(&[b"escrow"], &TOKEN_METADATA_PROGRAM_ID)
+            .0
+            .to_string();
+        let holders = vec![
+            holder(&bob, "m1"),
+            holder(&alice, "m3"),
+            holder(&alice, "m2"),
+            holder(&carol, "m4"),
+            holder(&escrow, "m5"),
+        ];
+
+        let options = LeaderboardOptions {
+            exclude: HashSet::from([carol.clone()]),
+            exclude_off_curve: true,
+        };
+        let leaderboard = holder_leaderboard(&holders, &options);
+
+        assert_eq!(leaderboard.len(), 2);
+        assert_eq!(leaderboard[0].owner_wallet, alice);
+        assert_eq!(leaderboard[0].mints, vec!["m2", "m3"]);
+        assert_eq!(leaderboard[0].percentage, 40.0);
+        assert_eq!(leaderboard[1].rank, 2);
+
+        let leaderboard = holder_leaderboard(&holders, &LeaderboardOptions::default());
+        assert_eq!(leaderboard.len(), 4);
+        assert_eq!(
+            leaderboard.iter().map(|w| w.rank).collect::<Vec<_>>(),
+            vec![1, 2, 2, 2]
+        );
+    }
+}
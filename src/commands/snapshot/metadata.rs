@@ -0,0 +1,284 @@
+use futures::stream::{self, StreamExt, TryStreamExt};
+use solana_program::program_pack::Pack;
+use spl_token::state::{Account as TokenAccount, Mint};
+use std::io::Write;
+
+use super::common::*;
+use super::data::*;
+use super::methods::get_metadata_accounts;
+
+use crate::commands::decode::{decode_edition_json, deserialize_metadata, JSONMetadata};
+use crate::constants::MAX_MULTIPLE_ACCOUNTS;
+use crate::derive::derive_edition_pda;
+use crate::output::{write_records, OutputFormat};
+use crate::parse::creator_is_verified;
+
+/// Snapshots the full metadata, edition, supply and holder of every matching mint. Fails if
+/// a mint's holder can't be fetched, so that a missing holder always means there is none.
+pub async fn snapshot_metadata(
+    client: &dyn AccountSource,
+    args: SnapshotMetadataArgs,
+) -> Result<Vec<MetadataRecord>> {
+    let accounts = get_metadata_accounts(
+        client,
+        &args.creator,
+        args.position,
+        &args.update_authority,
+        args.v2,
+    )
+    .await?;
+
+    let mut records = Vec::new();
+
+    for (metadata_pubkey, account) in accounts {
+        let metadata = match deserialize_metadata(&account.data) {
+            Ok(metadata) => metadata,
+            Err(_) => {
+                error!("Failed to parse metadata for account {}", metadata_pubkey);
+                continue;
+            }
+        };
+
+        if !creator_is_verified(&metadata.data.creators, args.position) && !args.allow_unverified {
+            continue;
+        }
+
+        let json = JSONMetadata::from(&metadata);
+        records.push(MetadataRecord {
+            mint: json.mint,
+            metadata_account: metadata_pubkey.to_string(),
+            update_authority: json.update_authority,
+            name: json.name,
+            symbol: json.symbol,
+            uri: json.uri,
+            seller_fee_basis_points: json.seller_fee_basis_points,
+            creators: json.creators.unwrap_or_default(),
+            collection: json.collection,
+            token_standard: json.token_standard,
+            edition: None,
+            supply: None,
+            holder: None,
+            primary_sale_happened: json.primary_sale_happened,
+            is_mutable: json.is_mutable,
+        });
+    }
+    records.sort_unstable_by(|a, b| a.mint.cmp(&b.mint));
+
+    info!("Fetching editions, supplies and holders...");
+    add_editions_and_supplies(client, &mut records).await?;
+    add_holders(client, &mut records).await?;
+
+    Ok(records)
+}
+
+/// Writes the records in `format`; CSV gets the flattened `MetadataCsvRecord` columns.
+pub fn write_metadata_snapshot<W: Write>(
+    writer: W,
+    records: &[MetadataRecord],
+    format: OutputFormat,
+) -> Result<()> {
+    match format {
+        OutputFormat::Csv => {
+            let rows: Vec<MetadataCsvRecord> =
+                records.iter().map(MetadataCsvRecord::from).collect();
+            write_records(writer, &rows, format)
+        }
+        _ => write_records(writer, records, format),
+    }
+}
+
+async fn add_editions_and_supplies(
+    client: &dyn AccountSource,
+    records: &mut [MetadataRecord],
+) -> Result<()> {
+    for chunk in records.chunks_mut(MAX_MULTIPLE_ACCOUNTS / 2) {
+        let mut pubkeys = Vec::new();
+        for record in chunk.iter() {
+            let mint = Pubkey::from_str(&record.mint)?;
+            pubkeys.push(mint);
+            pubkeys.push(derive_edition_pda(&mint));
+        }
+
+        let accounts = client.get_multiple_accounts(&pubkeys).await?;
+
+        for (record, accounts) in chunk.iter_mut().zip(accounts.chunks(2)) {
+            if let Some(mint_account) = &accounts[0] {
+                record.supply = Mint::unpack(&mint_account.data).ok().map(|m| m.supply);
+            }
+            if let Some(edition_account) = &accounts[1] {
+                record.edition = decode_edition_json(&edition_account.data).unwrap_or_else(|e| {
+                    error!("Failed to decode edition of {}: {}", record.mint, e);
+                    None
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn add_holders(client: &dyn AccountSource, records: &mut [MetadataRecord]) -> Result<()> {
+    let holders: Vec<Option<String>> = stream::iter(records.iter())
+        .map(|record| get_largest_holder(client, &record.mint))
+        .buffered(PARALLEL_LIMIT)
+        .try_collect()
+        .await?;
+
+    for (record, holder) in records.iter_mut().zip(holders) {
+        record.holder = holder;
+    }
+
+    Ok(())
+}
+
+/// Owner of the largest non-empty token account of `mint`, or `None` if every account is
+/// empty, e.g. because the NFT was burned.
+async fn get_largest_holder(client: &dyn AccountSource, mint: &str) -> Result<Option<String>> {
+    let mint_pubkey = Pubkey::from_str(mint)?;
+
+    let largest = client
+        .get_token_largest_accounts(&mint_pubkey)
+        .await
+        .map_err(|err| anyhow!("Failed to get token accounts of {}: {}", mint, err))?;
+    let token_account = match largest
+        .into_iter()
+        .find(|balance| balance.amount.amount != "0")
+    {
+        Some(token_account) => token_account,
+        None => return Ok(None),
+    };
+    let token_pubkey = Pubkey::from_str(&token_account.address)?;
+
+    let account = client
+        .get_account(&token_pubkey)
+        .await
+        .map_err(|err| anyhow!("Failed to get holder of {}: {}", mint, err))?
+        .ok_or_else(|| anyhow!("Token account {} of {} not found", token_pubkey, mint))?;
+    let token = TokenAccount::unpack(&account.data)
+        .map_err(|err| anyhow!("Invalid token account {}: {}", token_pubkey, err))?;
+
+    Ok(Some(token.owner.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account_source::{fixtures, MemoryAccountSource};
+    use borsh::BorshSerialize;
+    use futures::executor::block_on;
+    use mpl_token_metadata::state::{Key, MasterEditionV2};
+    use spl_token::state::AccountState;
+
+    fn account(owner: Pubkey, data: Vec<u8>) -> Account {
+        Account {
+            lamports: 1,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    fn args() -> SnapshotMetadataArgs {
+        SnapshotMetadataArgs {
+            creator: Some(fixtures::CREATOR.to_string()),
+            position: 0,
+            update_authority: None,
+            v2: false,
+            allow_unverified: false,
+        }
+    }
+
+    /// The fixture metadata with its mint, master edition and a token account of `holder`.
+    fn snapshot_source(holder: &Pubkey) -> MemoryAccountSource {
+        let mut source = fixtures::fixture_source();
+
+        let mut mint = vec![0; Mint::LEN];
+        Mint::pack(
+            Mint {
+                supply: 1,
+                is_initialized: true,
+                ..Default::default()
+            },
+            &mut mint,
+        )
+        .unwrap();
+        source.insert(fixtures::MINT, account(TOKEN_PROGRAM_ID, mint));
+
+        let mut token = vec![0; TokenAccount::LEN];
+        TokenAccount::pack(
+            TokenAccount {
+                mint: fixtures::MINT,
+                owner: *holder,
+                amount: 1,
+                state: AccountState::Initialized,
+                ..Default::default()
+            },
+            &mut token,
+        )
+        .unwrap();
+        source.insert(Pubkey::new_unique(), account(TOKEN_PROGRAM_ID, token));
+
+        let edition = MasterEditionV2 {
+            key: Key::MasterEditionV2,
+            supply: 0,
+            max_supply: Some(0),
+        };
+        source.insert(
+            derive_edition_pda(&fixtures::MINT),
+            account(TOKEN_METADATA_PROGRAM_ID, edition.try_to_vec().unwrap()),
+        );
+
+        source
+    }
+
+    #[test]
+    fn test_snapshot_metadata() {
+        let holder = Pubkey::new_unique();
+        let source = snapshot_source(&holder);
+
+        let records = block_on(snapshot_metadata(&source, args())).unwrap();
+        assert_eq!(records.len(), 1);
+
+        let json = serde_json::to_value(&records[0]).unwrap();
+        assert_eq!(json["mint"], fixtures::MINT.to_string());
+        assert_eq!(json["supply"], 1);
+        assert_eq!(json["holder"], holder.to_string());
+        assert_eq!(
+            json["edition"],
+            serde_json::json!({ "Master": { "supply": 0, "max_supply": 0 } })
+        );
+        assert_eq!(
+            json["creators"][0]["address"],
+            fixtures::CREATOR.to_string()
+        );
+        assert_eq!(
+            json["collection"]["key"],
+            fixtures::COLLECTION_MINT.to_string()
+        );
+
+        let mut csv = Vec::new();
+        write_metadata_snapshot(&mut csv, &records, OutputFormat::Csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "mint,metadata_account,update_authority,name,symbol,uri,seller_fee_basis_points,\
+             creators,collection,collection_verified,token_standard,edition_type,edition,\
+             max_supply,supply,holder,primary_sale_happened,is_mutable"
+        );
+        let row: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(row[7], format!("{}:100:true", fixtures::CREATOR));
+        assert_eq!(row[8], fixtures::COLLECTION_MINT.to_string());
+        assert_eq!(&row[11..16], ["master", "0", "0", "1", &holder.to_string()]);
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn test_snapshot_metadata_fails_without_holder_data() {
+        // Without the mint account the largest token accounts can't be looked up.
+        let source = fixtures::fixture_source();
+
+        assert!(block_on(snapshot_metadata(&source, args())).is_err());
+    }
+}
@@ -1,13 +1,18 @@
 use super::common::*;
 use super::data::*;
 
+use crate::commands::decode::deserialize_metadata;
 use crate::commands::theindexio;
 use crate::commands::theindexio::GPAResult;
-use crate::derive::derive_cmv2_pda;
+use crate::constants::*;
+use crate::derive::{derive_cmv2_pda, derive_metadata_pda};
+use crate::output::{output_file_path, write_records_to_file};
 use crate::parse::{creator_is_verified, is_only_one_option};
-use crate::{commands::decode::get_metadata_pda, constants::*};
 
-pub async fn snapshot_mints(client: &RpcClient, args: SnapshotMintsArgs) -> Result<Vec<String>> {
+pub async fn snapshot_mints(
+    client: &dyn AccountSource,
+    args: SnapshotMintsArgs,
+) -> Result<Vec<String>> {
     if !is_only_one_option(&args.creator, &args.update_authority) {
         return Err(anyhow!(
             "Please specify either a candy machine id or an update authority, but not both."
@@ -18,7 +23,7 @@ pub async fn snapshot_mints(client: &RpcClient, args: SnapshotMintsArgs) -> Resu
         client,
         &args.creator,
         args.position,
-        args.update_authority,
+        args.update_authority.clone(),
         args.allow_unverified,
         args.v2,
     )
@@ -26,9 +31,23 @@ pub async fn snapshot_mints(client: &RpcClient, args: SnapshotMintsArgs) -> Resu
 
     mint_addresses.sort_unstable();
 
+    if !args.output.is_empty() {
+        let prefix = output_prefix(&args.creator, &args.update_authority);
+        let path = output_file_path(&args.output, &format!("{}_mint_accounts", prefix));
+        write_records_to_file(path, &mint_addresses)?;
+    }
+
     Ok(mint_addresses)
 }
 
+/// Names output files after the creator or update authority that was snapshotted.
+fn output_prefix<'a>(creator: &'a Option<String>, update_authority: &'a Option<String>) -> &'a str {
+    creator
+        .as_deref()
+        .or(update_authority.as_deref())
+        .unwrap_or("mint_list")
+}
+
 pub async fn snapshot_indexed_mints(api_key: String, creator: &str) -> Result<Vec<String>> {
     let results =
         crate::commands::theindexio::get_verified_creator_accounts(api_key, creator).await?;
@@ -38,7 +57,7 @@ pub async fn snapshot_indexed_mints(api_key: String, creator: &str) -> Result<Ve
     for result in results {
         let bs64_data = &result.account.data.as_array().unwrap()[0];
         let data = base64::decode(bs64_data.as_str().unwrap())?;
-        let metadata: Metadata = match try_from_slice_unchecked(&data) {
+        let metadata = match deserialize_metadata(&data) {
             Ok(metadata) => metadata,
             Err(_) => {
                 error!("Failed to parse metadata for account {}", result.pubkey);
@@ -54,36 +73,20 @@ pub async fn snapshot_indexed_mints(api_key: String, creator: &str) -> Result<Ve
 }
 
 pub async fn get_mint_accounts(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     creator: &Option<String>,
     position: usize,
     update_authority: Option<String>,
     allow_unverified: bool,
     v2: bool,
 ) -> Result<Vec<String>> {
-    let accounts = if let Some(ref update_authority) = update_authority {
-        get_mints_by_update_authority(client, update_authority).await?
-    } else if let Some(ref creator) = creator {
-        // Support v2 cm ids
-        if v2 {
-            let creator_pubkey =
-                Pubkey::from_str(creator).expect("Failed to parse pubkey from creator!");
-            let cmv2_creator = derive_cmv2_pda(&creator_pubkey);
-            get_cm_creator_accounts(client, &cmv2_creator.to_string(), position).await?
-        } else {
-            get_cm_creator_accounts(client, creator, position).await?
-        }
-    } else {
-        return Err(anyhow!(
-            "Please specify either a candy machine id or an update authority, but not both."
-        ));
-    };
+    let accounts = get_metadata_accounts(client, creator, position, &update_authority, v2).await?;
 
     info!("Getting metadata and writing to file...");
     let mut mint_accounts: Vec<String> = Vec::new();
 
     for (pubkey, account) in accounts {
-        let metadata: Metadata = match try_from_slice_unchecked(&account.data) {
+        let metadata = match deserialize_metadata(&account.data) {
             Ok(metadata) => metadata,
             Err(_) => {
                 error!("Failed to parse metadata for account {}", pubkey);
@@ -99,22 +102,47 @@ pub async fn get_mint_accounts(
     Ok(mint_accounts)
 }
 
-pub async fn snapshot_holders(
-    client: &RpcClient,
-    args: SnapshotHoldersArgs,
-) -> Result<Vec<Holder>> {
-    let accounts = if let Some(ref update_authority) = args.update_authority {
-        get_mints_by_update_authority(client, update_authority).await?
-    } else if let Some(ref creator) = args.creator {
+/// Fetches the metadata accounts with the given update authority, or with `creator` at
+/// `position` in the creators array.
+pub async fn get_metadata_accounts(
+    client: &dyn AccountSource,
+    creator: &Option<String>,
+    position: usize,
+    update_authority: &Option<String>,
+    v2: bool,
+) -> Result<Vec<(Pubkey, Account)>> {
+    if let Some(ref update_authority) = update_authority {
+        get_mints_by_update_authority(client, update_authority).await
+    } else if let Some(ref creator) = creator {
         // Support v2 cm ids
-        if args.v2 {
+        if v2 {
             let creator_pubkey =
                 Pubkey::from_str(creator).expect("Failed to parse pubkey from creator!");
             let cmv2_creator = derive_cmv2_pda(&creator_pubkey);
-            get_cm_creator_accounts(client, &cmv2_creator.to_string(), args.position).await?
+            get_cm_creator_accounts(client, &cmv2_creator.to_string(), position).await
         } else {
-            get_cm_creator_accounts(client, creator, args.position).await?
+            get_cm_creator_accounts(client, creator, position).await
         }
+    } else {
+        Err(anyhow!(
+            "Please specify either a candy machine id or an update authority, but not both."
+        ))
+    }
+}
+
+pub async fn snapshot_holders(
+    client: &dyn AccountSource,
+    args: SnapshotHoldersArgs,
+) -> Result<Vec<Holder>> {
+    let accounts = if args.update_authority.is_some() || args.creator.is_some() {
+        get_metadata_accounts(
+            client,
+            &args.creator,
+            args.position,
+            &args.update_authority,
+            args.v2,
+        )
+        .await?
     } else if let Some(ref _mint_accounts_file) = args.mint_accounts_file {
         unimplemented!();
         /*
@@ -134,7 +162,7 @@ pub async fn snapshot_holders(
     for (metadata_pubkey, account) in accounts.iter() {
         let nft_holders = nft_holders.clone();
 
-        let metadata: Metadata = match try_from_slice_unchecked(&account.data) {
+        let metadata = match deserialize_metadata(&account.data) {
             Ok(metadata) => metadata,
             Err(_) => {
                 error!("Account {} has no metadata", metadata_pubkey);
@@ -218,6 +246,12 @@ pub async fn snapshot_holders(
     nft_holders.lock().unwrap().sort_unstable();
     let nft_holders = nft_holders.lock().unwrap().clone();
 
+    if !args.output.is_empty() {
+        let prefix = output_prefix(&args.creator, &args.update_authority);
+        let path = output_file_path(&args.output, &format!("{}_holders", prefix));
+        write_records_to_file(path, &nft_holders)?;
+    }
+
     Ok(nft_holders)
 }
 
@@ -269,7 +303,7 @@ pub async fn snapshot_indexed_holders(api_key: String, creator: &str) -> Result<
 pub async fn get_holder_from_gpa_result(api_key: String, result: GPAResult) -> Result<Holder> {
     let bs64_data = &result.account.data.as_array().unwrap()[0];
     let data = base64::decode(bs64_data.as_str().unwrap())?;
-    let metadata: Metadata = match try_from_slice_unchecked(&data) {
+    let metadata = match deserialize_metadata(&data) {
         Ok(metadata) => metadata,
         Err(_) => {
             return Err(anyhow!(
@@ -342,7 +376,7 @@ pub async fn get_holder_from_gpa_result(api_key: String, result: GPAResult) -> R
 }
 
 pub async fn get_mint_account_infos(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     mint_accounts: Vec<String>,
 ) -> Result<Vec<(Pubkey, Account)>> {
     let address_account_pairs: Arc<Mutex<Vec<(Pubkey, Account)>>> =
@@ -356,12 +390,12 @@ pub async fn get_mint_account_infos(
                 continue;
             }
         };
-        
-        let metadata_pubkey = get_metadata_pda(&mint_pubkey);
+
+        let metadata_pubkey = derive_metadata_pda(&mint_pubkey);
 
         let account_info = match client.get_account(&metadata_pubkey).await {
-            Ok(account) => account,
-            Err(_) => {
+            Ok(Some(account)) => account,
+            _ => {
                 error!("Error in fetching metadata for mint {}", mint_account);
                 continue;
             }
@@ -378,7 +412,7 @@ pub async fn get_mint_account_infos(
 }
 
 async fn get_mints_by_update_authority(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     update_authority: &str,
 ) -> Result<Vec<(Pubkey, Account)>> {
     #[allow(deprecated)]
@@ -408,7 +442,7 @@ async fn get_mints_by_update_authority(
 }
 
 pub async fn snapshot_cm_accounts(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     update_authority: &str,
 ) -> Result<CandyMachineProgramAccounts> {
     let accounts = get_cm_accounts_by_update_authority(client, update_authority).await?;
@@ -441,7 +475,7 @@ pub async fn snapshot_cm_accounts(
 }
 
 async fn get_cm_accounts_by_update_authority(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     update_authority: &str,
 ) -> Result<Vec<(Pubkey, Account)>> {
     let candy_machine_program_id = Pubkey::from_str(CANDY_MACHINE_PROGRAM_ID)?;
@@ -472,7 +506,7 @@ async fn get_cm_accounts_by_update_authority(
 }
 
 pub async fn get_cm_creator_accounts(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     creator: &str,
     position: usize,
 ) -> Result<Vec<(Pubkey, Account)>> {
@@ -524,8 +558,8 @@ pub async fn get_cm_creator_accounts(
     Ok(accounts)
 }
 
-async fn get_holder_token_accounts(
-    client: &RpcClient,
+pub async fn get_holder_token_accounts(
+    client: &dyn AccountSource,
     mint_account: String,
 ) -> Result<Vec<(Pubkey, Account)>> {
     #[allow(deprecated)]
@@ -584,3 +618,36 @@ fn parse_owner(data: &ParsedAccount) -> Result<String> {
         .to_string();
     Ok(owner)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account_source::fixtures::*;
+    use futures::executor::block_on;
+    use solana_sdk::signer::Signer;
+
+    #[test]
+    fn test_get_mint_accounts_from_account_source() {
+        let source = fixture_source();
+        let mints = vec![MINT.to_string()];
+
+        let by_update_authority = Some(update_authority().pubkey().to_string());
+        let found = block_on(get_mint_accounts(
+            &source,
+            &None,
+            0,
+            by_update_authority,
+            false,
+            false,
+        ))
+        .unwrap();
+        assert_eq!(found, mints);
+
+        let creator = Some(CREATOR.to_string());
+        let found = block_on(get_mint_accounts(&source, &creator, 0, None, false, false)).unwrap();
+        assert_eq!(found, mints);
+
+        let found = block_on(get_mint_accounts(&source, &creator, 1, None, true, false)).unwrap();
+        assert!(found.is_empty());
+    }
+}
@@ -1,5 +1,15 @@
+mod balances;
 mod common;
 mod data;
+mod leaderboard;
+mod metadata;
 mod methods;
+mod resolver;
+mod stream;
+pub use balances::*;
 pub use data::*;
+pub use leaderboard::*;
+pub use metadata::*;
 pub use methods::*;
+pub use resolver::*;
+pub use stream::*;
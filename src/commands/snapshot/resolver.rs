@@ -0,0 +1,512 @@
+use async_trait::async_trait;
+use solana_sdk::system_program;
+use std::collections::HashMap;
+
+use super::common::*;
+use super::data::*;
+
+use crate::constants::{
+    CARDINAL_STAKE_POOL_PROGRAM_ID, CARDINAL_TOKEN_MANAGER_PROGRAM_ID, MAX_MULTIPLE_ACCOUNTS,
+};
+
+/// Anchor discriminator of Cardinal's `StakeEntry` account.
+const STAKE_ENTRY_DISCRIMINATOR: [u8; 8] = [187, 127, 9, 35, 155, 68, 86, 40];
+/// Anchor discriminator of Cardinal's `TokenManager` account.
+const TOKEN_MANAGER_DISCRIMINATOR: [u8; 8] = [185, 97, 124, 231, 70, 75, 228, 47];
+
+/// Finds the wallet behind a token owner that is a program account or PDA, such as a
+/// staking vault or marketplace escrow.
+#[async_trait]
+pub trait OwnerResolver: Send + Sync {
+    /// The program whose accounts this resolver decodes.
+    fn program_id(&self) -> Pubkey;
+
+    /// Returns the depositor of `mint` held by `owner`, or `None` if this resolver doesn't
+    /// recognise the owner. `owner_account` is `None` when the owner has no account.
+    async fn resolve(
+        &self,
+        client: &dyn AccountSource,
+        owner: &Pubkey,
+        owner_account: Option<&Account>,
+        mint: &Pubkey,
+    ) -> Result<Option<Pubkey>>;
+}
+
+/// Resolves owners that are accounts of `program_id` storing the depositor at
+/// `depositor_offset`, e.g. an escrow state account that owns the escrowed token.
+pub struct AccountFieldResolver {
+    pub program_id: Pubkey,
+    /// Leading bytes the account data must start with, such as an Anchor discriminator.
+    pub discriminator: Option<Vec<u8>>,
+    /// Offset of the escrowed mint, checked against the holder's mint when set.
+    pub mint_offset: Option<usize>,
+    pub depositor_offset: usize,
+}
+
+impl AccountFieldResolver {
+    /// Cardinal staking, which moves a staked token to an account owned by its `StakeEntry`.
+    /// The depositor is the entry's `last_staker`.
+    pub fn cardinal_stake_entry() -> Self {
+        Self {
+            program_id: Pubkey::from_str(CARDINAL_STAKE_POOL_PROGRAM_ID).expect("valid program id"),
+            discriminator: Some(STAKE_ENTRY_DISCRIMINATOR.to_vec()),
+            // discriminator, bump, pool, amount
+            mint_offset: Some(8 + 1 + 32 + 8),
+            // ... original_mint, original_mint_claimed
+            depositor_offset: 8 + 1 + 32 + 8 + 32 + 1,
+        }
+    }
+
+    /// Cardinal token manager, which escrows an issued token in an account owned by its
+    /// `TokenManager` until it is claimed. The depositor is the manager's `issuer`.
+    pub fn cardinal_token_manager() -> Self {
+        Self {
+            program_id: Pubkey::from_str(CARDINAL_TOKEN_MANAGER_PROGRAM_ID)
+                .expect("valid program id"),
+            discriminator: Some(TOKEN_MANAGER_DISCRIMINATOR.to_vec()),
+            // discriminator, version, bump, count, num_invalidators, issuer
+            mint_offset: Some(8 + 1 + 1 + 8 + 1 + 32),
+            depositor_offset: 8 + 1 + 1 + 8 + 1,
+        }
+    }
+}
+
+#[async_trait]
+impl OwnerResolver for AccountFieldResolver {
+    fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    async fn resolve(
+        &self,
+        _client: &dyn AccountSource,
+        _owner: &Pubkey,
+        owner_account: Option<&Account>,
+        mint: &Pubkey,
+    ) -> Result<Option<Pubkey>> {
+        let account = match owner_account {
+            Some(account) if account.owner == self.program_id => account,
+            _ => return Ok(None),
+        };
+
+        if let Some(ref discriminator) = self.discriminator {
+            if !account.data.starts_with(discriminator) {
+                return Ok(None);
+            }
+        }
+        if let Some(mint_offset) = self.mint_offset {
+            if read_pubkey(&account.data, mint_offset) != Some(*mint) {
+                return Ok(None);
+            }
+        }
+
+        Ok(read_pubkey(&account.data, self.depositor_offset))
+    }
+}
+
+/// Resolves owners through a separate record account of `program_id` that references the
+/// mint at `mint_offset` and the depositor at `depositor_offset`, e.g. a stake entry next to a
+/// vault PDA that holds the tokens of many stakers.
+///
+/// Records are matched on the token owner too when `owner_offset` is set. The owner is left
+/// unresolved if the matching records name more than one depositor.
+pub struct MintRecordResolver {
+    pub program_id: Pubkey,
+    pub mint_offset: usize,
+    /// Offset of the vault or escrow that owns the token.
+    pub owner_offset: Option<usize>,
+    pub depositor_offset: usize,
+    pub data_size: Option<u64>,
+}
+
+#[async_trait]
+impl OwnerResolver for MintRecordResolver {
+    fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    async fn resolve(
+        &self,
+        client: &dyn AccountSource,
+        owner: &Pubkey,
+        _owner_account: Option<&Account>,
+        mint: &Pubkey,
+    ) -> Result<Option<Pubkey>> {
+        let mut filters = vec![memcmp(self.mint_offset, mint)];
+        if let Some(owner_offset) = self.owner_offset {
+            filters.push(memcmp(owner_offset, owner));
+        }
+        if let Some(data_size) = self.data_size {
+            filters.push(RpcFilterType::DataSize(data_size));
+        }
+
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: None,
+                commitment: Some(CommitmentConfig {
+                    commitment: CommitmentLevel::Confirmed,
+                }),
+                min_context_slot: None,
+            },
+            with_context: None,
+        };
+
+        let records = client
+            .get_program_accounts_with_config(&self.program_id, config)
+            .await?;
+
+        let mut depositors: Vec<Pubkey> = records
+            .iter()
+            .filter_map(|(_, record)| read_pubkey(&record.data, self.depositor_offset))
+            .collect();
+        depositors.sort_unstable();
+        depositors.dedup();
+
+        match depositors[..] {
+            [depositor] => Ok(Some(depositor)),
+            [] => Ok(None),
+            _ => {
+                debug!(
+                    "{} records of {} name different depositors of {}",
+                    records.len(),
+                    self.program_id,
+                    mint
+                );
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ResolvedHolder {
+    /// `owner_wallet` is the depositor when the owner was resolved.
+    #[serde(flatten)]
+    pub holder: Holder,
+    /// The PDA or program account that owns the token account, if it was resolved.
+    pub escrow_account: Option<String>,
+    /// Program owning the escrow, or owning the unresolved owner account.
+    pub owner_program: Option<String>,
+    /// Why the owner couldn't be resolved, if fetching its accounts failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Registered resolvers, tried in order for every off-curve owner.
+#[derive(Default)]
+pub struct OwnerResolvers {
+    resolvers: Vec<Box<dyn OwnerResolver>>,
+}
+
+impl OwnerResolvers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in resolvers for the Cardinal staking and token manager programs.
+    pub fn with_presets() -> Self {
+        let mut resolvers = Self::new();
+        resolvers.register(AccountFieldResolver::cardinal_stake_entry());
+        resolvers.register(AccountFieldResolver::cardinal_token_manager());
+        resolvers
+    }
+
+    pub fn register<R: OwnerResolver + 'static>(&mut self, resolver: R) {
+        self.resolvers.push(Box::new(resolver));
+    }
+
+    /// Replaces PDA and program-account owners with the wallets behind them.
+    ///
+    /// Owners on the ed25519 curve with no account or a system account are wallets and are
+    /// kept as they are. If fetching an owner's accounts fails, its holders are left
+    /// unresolved with the error rather than failing the whole snapshot.
+    pub async fn resolve_holders(
+        &self,
+        client: &dyn AccountSource,
+        holders: Vec<Holder>,
+    ) -> Result<Vec<ResolvedHolder>> {
+        let mut owners = holders
+            .iter()
+            .map(|holder| Pubkey::from_str(&holder.owner_wallet))
+            .collect::<Result<Vec<_>, _>>()?;
+        owners.sort_unstable();
+        owners.dedup();
+        let owner_accounts = get_owner_accounts(client, &owners).await;
+
+        let mut resolved = Vec::with_capacity(holders.len());
+
+        for mut holder in holders {
+            let owner = Pubkey::from_str(&holder.owner_wallet)?;
+            let unresolved =
+                |holder: Holder, owner_program: Option<String>, error| ResolvedHolder {
+                    holder,
+                    escrow_account: None,
+                    owner_program,
+                    error,
+                };
+
+            let owner_account = match &owner_accounts[&owner] {
+                Ok(account) => account.as_ref(),
+                Err(err) => {
+                    resolved.push(unresolved(holder, None, Some(err.clone())));
+                    continue;
+                }
+            };
+            let owner_program = owner_account.map(|a| a.owner.to_string());
+
+            let is_wallet = !matches!(owner_account, Some(a) if a.owner != system_program::ID);
+            if owner.is_on_curve() && is_wallet {
+                resolved.push(unresolved(holder, None, None));
+                continue;
+            }
+
+            let mint = Pubkey::from_str(&holder.mint_account)?;
+            let mut resolution = Ok(None);
+            for resolver in &self.resolvers {
+                resolution = resolver
+                    .resolve(client, &owner, owner_account, &mint)
+                    .await
+                    .map(|depositor| depositor.map(|d| (d, resolver.program_id())));
+                if !matches!(resolution, Ok(None)) {
+                    break;
+                }
+            }
+
+            match resolution {
+                Ok(Some((depositor, program_id))) => {
+                    holder.owner_wallet = depositor.to_string();
+                    resolved.push(ResolvedHolder {
+                        holder,
+                        escrow_account: Some(owner.to_string()),
+                        owner_program: Some(program_id.to_string()),
+                        error: None,
+                    });
+                }
+                Ok(None) => {
+                    debug!("No resolver for owner {} of {}", owner, mint);
+                    resolved.push(unresolved(holder, owner_program, None));
+                }
+                Err(err) => {
+                    error!("Failed to resolve owner {} of {}: {}", owner, mint, err);
+                    resolved.push(unresolved(holder, owner_program, Some(err.to_string())));
+                }
+            }
+        }
+
+        Ok(resolved)
+    }
+}
+
+/// Fetches owner accounts in batches, recording a failed batch's error for each of its owners.
+async fn get_owner_accounts(
+    client: &dyn AccountSource,
+    owners: &[Pubkey],
+) -> HashMap<Pubkey, Result<Option<Account>, String>> {
+    let mut accounts = HashMap::with_capacity(owners.len());
+
+    for chunk in owners.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        match client.get_multiple_accounts(chunk).await {
+            Ok(chunk_accounts) => accounts.extend(
+                chunk
+                    .iter()
+                    .copied()
+                    .zip(chunk_accounts.into_iter().map(Ok)),
+            ),
+            Err(err) => {
+                error!("Failed to fetch {} owner accounts: {}", chunk.len(), err);
+                accounts.extend(chunk.iter().map(|owner| (*owner, Err(err.to_string()))));
+            }
+        }
+    }
+
+    accounts
+}
+
+fn memcmp(offset: usize, pubkey: &Pubkey) -> RpcFilterType {
+    #[allow(deprecated)]
+    RpcFilterType::Memcmp(Memcmp {
+        offset,
+        bytes: MemcmpEncodedBytes::Base58(pubkey.to_string()),
+        encoding: None,
+    })
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Option<Pubkey> {
+    data.get(offset..offset + 32)
+        .and_then(|bytes| Pubkey::try_from(bytes).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account_source::MemoryAccountSource;
+    use futures::executor::block_on;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn test_account_field_resolver() {
+        let program_id = Pubkey::new_unique();
+        let depositor = Pubkey::new_unique();
+        let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        data.extend_from_slice(depositor.as_ref());
+        let account = Account {
+            lamports: 1,
+            data,
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        };
+
+        let resolver = AccountFieldResolver {
+            program_id,
+            discriminator: Some(vec![1, 2, 3, 4, 5, 6, 7, 8]),
+            mint_offset: None,
+            depositor_offset: 8,
+        };
+        // Never read by this resolver.
+        let client = MemoryAccountSource::new();
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+
+        let resolve =
+            |account: &Account| block_on(resolver.resolve(&client, &owner, Some(account), &mint));
+
+        assert_eq!(resolve(&account).unwrap(), Some(depositor));
+
+        let other_program = Account {
+            owner: Pubkey::new_unique(),
+            ..account.clone()
+        };
+        assert_eq!(resolve(&other_program).unwrap(), None);
+
+        let other_layout = Account {
+            data: vec![0; 40],
+            ..account
+        };
+        assert_eq!(resolve(&other_layout).unwrap(), None);
+    }
+
+    struct FailingResolver;
+
+    #[async_trait]
+    impl OwnerResolver for FailingResolver {
+        fn program_id(&self) -> Pubkey {
+            Pubkey::default()
+        }
+
+        async fn resolve(
+            &self,
+            _client: &dyn AccountSource,
+            _owner: &Pubkey,
+            _owner_account: Option<&Account>,
+            _mint: &Pubkey,
+        ) -> Result<Option<Pubkey>> {
+            Err(anyhow!("connection reset"))
+        }
+    }
+
+    fn holder(owner: &Pubkey, mint: &Pubkey) -> Holder {
+        Holder {
+            owner_wallet: owner.to_string(),
+            mint_account: mint.to_string(),
+            metadata_account: String::new(),
+            associated_token_address: String::new(),
+        }
+    }
+
+    fn account(owner: Pubkey, data: Vec<u8>) -> Account {
+        Account {
+            lamports: 1,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn test_resolve_holders() {
+        let mint = Pubkey::new_unique();
+        let depositor = Pubkey::new_unique();
+        let wallet = Keypair::new().pubkey();
+        // On the curve, but owned by the staking program.
+        let stake_entry = Keypair::new().pubkey();
+        let unknown = Pubkey::find_program_address(&[b"vault"], &Pubkey::new_unique()).0;
+        let unknown_program = Pubkey::new_unique();
+
+        let resolver = AccountFieldResolver::cardinal_stake_entry();
+        let mut data = STAKE_ENTRY_DISCRIMINATOR.to_vec();
+        data.resize(resolver.depositor_offset + 32, 0);
+        let mint_offset = resolver.mint_offset.unwrap();
+        data[mint_offset..mint_offset + 32].copy_from_slice(mint.as_ref());
+        data[resolver.depositor_offset..].copy_from_slice(depositor.as_ref());
+
+        let mut client = MemoryAccountSource::new();
+        client.insert(wallet, account(system_program::ID, Vec::new()));
+        client.insert(stake_entry, account(resolver.program_id, data));
+        client.insert(unknown, account(unknown_program, Vec::new()));
+
+        let holders = vec![
+            holder(&wallet, &mint),
+            holder(&stake_entry, &mint),
+            holder(&unknown, &mint),
+        ];
+        let resolved =
+            block_on(OwnerResolvers::with_presets().resolve_holders(&client, holders)).unwrap();
+
+        assert_eq!(resolved[0].holder.owner_wallet, wallet.to_string());
+        assert!(resolved[0].escrow_account.is_none());
+        assert_eq!(resolved[1].holder.owner_wallet, depositor.to_string());
+        assert_eq!(resolved[1].escrow_account, Some(stake_entry.to_string()));
+        assert_eq!(resolved[2].holder.owner_wallet, unknown.to_string());
+        assert_eq!(resolved[2].owner_program, Some(unknown_program.to_string()));
+
+        let mut failing = OwnerResolvers::new();
+        failing.register(FailingResolver);
+        let holders = vec![holder(&unknown, &mint), holder(&wallet, &mint)];
+        let resolved = block_on(failing.resolve_holders(&client, holders)).unwrap();
+
+        assert_eq!(resolved[0].error.as_deref(), Some("connection reset"));
+        assert!(resolved[1].error.is_none());
+    }
+
+    #[test]
+    fn test_mint_record_resolver() {
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let depositor = Pubkey::new_unique();
+
+        let record = |vault: &Pubkey, depositor: &Pubkey| {
+            account(
+                program_id,
+                [mint.as_ref(), vault.as_ref(), depositor.as_ref()].concat(),
+            )
+        };
+        let mut client = MemoryAccountSource::new();
+        client.insert(Pubkey::new_unique(), record(&vault, &depositor));
+        // A stale record of the same mint in another vault.
+        client.insert(
+            Pubkey::new_unique(),
+            record(&Pubkey::new_unique(), &Pubkey::new_unique()),
+        );
+
+        let mut resolver = MintRecordResolver {
+            program_id,
+            mint_offset: 0,
+            owner_offset: Some(32),
+            depositor_offset: 64,
+            data_size: None,
+        };
+        let resolve = |resolver: &MintRecordResolver| {
+            block_on(resolver.resolve(&client, &vault, None, &mint)).unwrap()
+        };
+
+        assert_eq!(resolve(&resolver), Some(depositor));
+
+        resolver.owner_offset = None;
+        assert_eq!(resolve(&resolver), None);
+    }
+}
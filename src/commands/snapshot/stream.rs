@@ -0,0 +1,270 @@
+use reqwest::StatusCode;
+use solana_account_decoder::UiDataSliceConfig;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use std::io::Write;
+
+use super::common::*;
+use super::data::*;
+
+use crate::constants::{MAX_CREATOR_LEN, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH};
+use crate::derive::derive_cmv2_pda;
+use crate::output::RecordWriter;
+use mpl_token_metadata::state::MAX_CREATOR_LIMIT;
+
+// Metadata strings are padded to their maximum length, so these offsets are fixed.
+const MINT_OFFSET: usize = 1 + 32;
+const CREATORS_OPTION_OFFSET: usize = MINT_OFFSET
+    + 32 // mint
+    + 4 + MAX_NAME_LENGTH
+    + 4 + MAX_SYMBOL_LENGTH
+    + 4 + MAX_URI_LENGTH
+    + 2; // seller fee basis points
+const CREATORS_OFFSET: usize = CREATORS_OPTION_OFFSET + 1 + 4;
+
+/// Shards are split on up to this many leading bytes of the mint.
+const MAX_SHARD_DEPTH: usize = 2;
+
+/// Fragments of the errors RPC nodes return when a scan is too large or too slow.
+const SPLIT_ERROR_MESSAGES: &[&str] = &[
+    "scan aborted",
+    "exceeded the limit",
+    "too large",
+    "timed out",
+    "timeout",
+];
+
+/// Fragments of rate limiting errors, which splitting into more queries only makes worse.
+const RATE_LIMIT_MESSAGES: &[&str] = &["429", "too many requests", "rate limit"];
+
+/// Snapshots mints like `snapshot_mints`, writing each one as soon as its shard is fetched
+/// instead of collecting and sorting them. Returns the number of mints written.
+///
+/// Only the mint and creator bytes of each metadata account are fetched. If the RPC node
+/// rejects a query's response size or scan limit, or it times out, the query is split into
+/// 256 queries on the next byte of the mint address. Any other error is returned.
+pub async fn snapshot_mints_streaming<W: Write>(
+    client: &dyn AccountSource,
+    args: &StreamSnapshotArgs,
+    writer: W,
+) -> Result<usize> {
+    let filter = if let Some(ref update_authority) = args.update_authority {
+        memcmp(1, Pubkey::from_str(update_authority)?.as_ref())
+    } else if let Some(ref creator) = args.creator {
+        let mut creator = Pubkey::from_str(creator)?;
+        // Support v2 cm ids
+        if args.v2 {
+            creator = derive_cmv2_pda(&creator);
+        }
+        memcmp(
+            CREATORS_OFFSET + args.position * MAX_CREATOR_LEN,
+            creator.as_ref(),
+        )
+    } else {
+        return Err(anyhow!(
+            "Please specify either a candy machine id or an update authority."
+        ));
+    };
+
+    let data_slice = UiDataSliceConfig {
+        offset: MINT_OFFSET,
+        length: if args.allow_unverified {
+            32
+        } else {
+            CREATORS_OFFSET + MAX_CREATOR_LIMIT * MAX_CREATOR_LEN - MINT_OFFSET
+        },
+    };
+
+    let mut writer = RecordWriter::new(writer, args.format);
+    let mut count = 0;
+    let mut shards: Vec<Vec<u8>> = vec![Vec::new()];
+
+    while let Some(prefix) = shards.pop() {
+        let mut filters = vec![filter.clone()];
+        if !prefix.is_empty() {
+            filters.push(memcmp(MINT_OFFSET, &prefix));
+        }
+
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: Some(data_slice),
+                commitment: Some(CommitmentConfig {
+                    commitment: CommitmentLevel::Confirmed,
+                }),
+                min_context_slot: None,
+            },
+            with_context: None,
+        };
+
+        let accounts = match client
+            .get_program_accounts_with_config(&TOKEN_METADATA_PROGRAM_ID, config)
+            .await
+        {
+            Ok(accounts) => accounts,
+            Err(err) if prefix.len() < MAX_SHARD_DEPTH && is_split_error(&err) => {
+                info!("Splitting shard {:?}: {}", prefix, err);
+                shards.extend(
+                    (0..=u8::MAX)
+                        .rev()
+                        .map(|b| [prefix.as_slice(), &[b]].concat()),
+                );
+                continue;
+            }
+            Err(err) => return Err(anyhow!("Shard {:?} failed: {}", prefix, err)),
+        };
+
+        for (metadata_pubkey, account) in accounts {
+            if let Some(mint) =
+                parse_mint_slice(&account.data, args.position, args.allow_unverified)
+            {
+                writer.write(&StreamedMint {
+                    mint_account: mint.to_string(),
+                    metadata_account: metadata_pubkey.to_string(),
+                })?;
+                count += 1;
+            }
+        }
+        debug!("Shard {:?} done, {} mints so far", prefix, count);
+    }
+
+    writer.finish()?;
+
+    Ok(count)
+}
+
+/// Reads the mint from a metadata data slice starting at the mint, returning `None` unless
+/// the creator at `position` is verified or `allow_unverified` is set.
+pub fn parse_mint_slice(data: &[u8], position: usize, allow_unverified: bool) -> Option<Pubkey> {
+    let mint = Pubkey::try_from(data.get(..32)?).ok()?;
+    if allow_unverified {
+        return Some(mint);
+    }
+
+    let has_creators = *data.get(CREATORS_OPTION_OFFSET - MINT_OFFSET)? == 1;
+    let len_offset = CREATORS_OPTION_OFFSET + 1 - MINT_OFFSET;
+    let creators_len = u32::from_le_bytes(data.get(len_offset..len_offset + 4)?.try_into().ok()?);
+    if !has_creators || position >= creators_len as usize {
+        return None;
+    }
+
+    let verified_offset = CREATORS_OFFSET - MINT_OFFSET + position * MAX_CREATOR_LEN + 32;
+    (*data.get(verified_offset)? == 1).then_some(mint)
+}
+
+/// Whether a failed query may succeed when split into smaller ones. Rate limited queries
+/// are not split.
+fn is_split_error(err: &anyhow::Error) -> bool {
+    if let Some(ClientError {
+        kind: ClientErrorKind::Reqwest(err),
+        ..
+    }) = err.downcast_ref::<ClientError>()
+    {
+        if err.status() == Some(StatusCode::TOO_MANY_REQUESTS) {
+            return false;
+        }
+        if err.is_timeout()
+            || matches!(
+                err.status(),
+                Some(StatusCode::PAYLOAD_TOO_LARGE | StatusCode::GATEWAY_TIMEOUT)
+            )
+        {
+            return true;
+        }
+    }
+
+    let message = err.to_string().to_lowercase();
+    if RATE_LIMIT_MESSAGES
+        .iter()
+        .any(|fragment| message.contains(fragment))
+    {
+        return false;
+    }
+    SPLIT_ERROR_MESSAGES
+        .iter()
+        .any(|fragment| message.contains(fragment))
+}
+
+fn memcmp(offset: usize, bytes: &[u8]) -> RpcFilterType {
+    #[allow(deprecated)]
+    RpcFilterType::Memcmp(Memcmp {
+        offset,
+        bytes: MemcmpEncodedBytes::Base58(bs58::encode(bytes).into_string()),
+        encoding: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use mpl_token_metadata::state::{Creator, Data, Key, Metadata};
+
+    #[test]
+    fn test_parse_mint_slice() {
+        let mint = Pubkey::new_unique();
+        let metadata = Metadata {
+            key: Key::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint,
+            data: Data {
+                name: format!("{:\0<32}", "Item"),
+                symbol: format!("{:\0<10}", "ITM"),
+                uri: format!("{:\0<200}", "https://example.com/0.json"),
+                seller_fee_basis_points: 500,
+                creators: Some(vec![
+                    Creator {
+                        address: Pubkey::new_unique(),
+                        verified: true,
+                        share: 0,
+                    },
+                    Creator {
+                        address: Pubkey::new_unique(),
+                        verified: false,
+                        share: 100,
+                    },
+                ]),
+            },
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: None,
+            collection: None,
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        };
+        let data = metadata.try_to_vec().unwrap();
+        let slice = &data[MINT_OFFSET..];
+
+        assert_eq!(parse_mint_slice(slice, 0, false), Some(mint));
+        assert_eq!(parse_mint_slice(slice, 1, false), None);
+        assert_eq!(parse_mint_slice(slice, 1, true), Some(mint));
+        assert_eq!(parse_mint_slice(slice, 2, false), None);
+    }
+
+    #[test]
+    fn test_is_split_error() {
+        let rpc_error = |message: &str| {
+            anyhow::Error::from(ClientError::from(ClientErrorKind::Custom(
+                message.to_string(),
+            )))
+        };
+
+        assert!(is_split_error(&rpc_error(
+            "scan aborted: The accumulated scan results exceeded the limit"
+        )));
+        assert!(is_split_error(&anyhow!("operation timed out")));
+        assert!(!is_split_error(&rpc_error(
+            "Invalid param: could not find account"
+        )));
+        assert!(!is_split_error(&anyhow!("Invalid filter")));
+        assert!(!is_split_error(&rpc_error(
+            "HTTP status client error (429 Too Many Requests)"
+        )));
+        assert!(!is_split_error(&rpc_error("rate limit exceeded")));
+        assert!(!is_split_error(&anyhow!(
+            "Too many requests for a specific RPC call, contact your app developer"
+        )));
+    }
+}
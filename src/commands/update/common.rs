@@ -2,14 +2,18 @@ pub use anyhow::{anyhow, Result as AnyResult};
 pub use async_trait::async_trait;
 pub use log::{debug, error, info, warn};
 pub use mpl_token_metadata::{
-    instruction::update_metadata_accounts_v2, state::DataV2, ID as TOKEN_METADATA_PROGRAM_ID,
+    instruction::update_metadata_accounts_v2,
+    state::{Creator, DataV2, MAX_CREATOR_LIMIT},
+    ID as TOKEN_METADATA_PROGRAM_ID,
 };
-pub use solana_client::nonblocking::rpc_client::RpcClient;
+pub use crate::account_source::AccountSource;
 pub use solana_sdk::{
     pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction,
 };
 pub use std::{cmp, fmt::Display, str::FromStr, sync::Arc};
 
-pub use crate::commands::decode::{decode, get_metadata_pda};
+pub use super::patch::{update_metadata, MetadataPatch};
+pub use crate::commands::decode::decode;
+pub use crate::derive::derive_metadata_pda;
 pub use crate::errors::ActionError;
 pub use crate::parse::parse_cli_creators;
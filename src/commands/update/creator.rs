@@ -1,11 +1,12 @@
 use crate::parse::{keypair::parse_keypair, solana_config::parse_solana_config};
+use crate::validate::validate_creators;
 
-use super::{common::*, update_data};
+use super::common::*;
 
 pub type UpdateResults = Vec<Result<(), ActionError>>;
 
 pub async fn update_creator_by_position(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     keypair_path: Option<String>,
     mint_account: &Pubkey,
     new_creators: &str,
@@ -15,112 +16,262 @@ pub async fn update_creator_by_position(
     let keypair = parse_keypair(keypair_path, solana_opts)?;
 
     let old_md = decode(client, mint_account).await?;
-    let data_with_old_creators = old_md.data;
+    let patch = MetadataPatch {
+        creators: Some(merge_creators(
+            old_md.data.creators.clone(),
+            new_creators,
+            should_append,
+        )?),
+        ..Default::default()
+    };
+
+    update_metadata(client, &keypair, &old_md, &patch).await
+}
+
+pub async fn update_creator(
+    client: &dyn AccountSource,
+    keypair: Keypair,
+    mint_account: Pubkey,
+    new_creators: String,
+    should_append: bool,
+) -> Result<Transaction, ActionError> {
+    let action_failed = |e: String| ActionError::ActionFailed(mint_account.to_string(), e);
+
+    let old_md = decode(client, &mint_account)
+        .await
+        .map_err(|e| action_failed(e.to_string()))?;
+    let patch = MetadataPatch {
+        creators: Some(
+            merge_creators(old_md.data.creators.clone(), &new_creators, should_append)
+                .map_err(|e| action_failed(e.to_string()))?,
+        ),
+        ..Default::default()
+    };
+
+    update_metadata(client, &keypair, &old_md, &patch)
+        .await
+        .map_err(|e| action_failed(e.to_string()))
+}
+
+/// Replaces the creators with the parsed CLI creators, or appends them with shares of 0.
+fn merge_creators(
+    old_creators: Option<Vec<Creator>>,
+    new_creators: &str,
+    should_append: bool,
+) -> AnyResult<Vec<Creator>> {
     let parsed_creators = parse_cli_creators(new_creators.to_string(), should_append)?;
 
-    let new_creators = if let Some(mut old_creators) = data_with_old_creators.creators {
-        if !should_append {
-            parsed_creators
-        } else {
-            let remaining_space = 5 - old_creators.len();
+    let new_creators = match old_creators {
+        Some(mut old_creators) if should_append => {
+            let remaining_space = MAX_CREATOR_LIMIT.saturating_sub(old_creators.len());
             warn!(
                 "Appending {} new creators with old creators with shares of 0",
                 parsed_creators.len()
             );
             let end_index = cmp::min(parsed_creators.len(), remaining_space);
-            old_creators.append(&mut parsed_creators[0..end_index].to_vec());
+            old_creators.extend_from_slice(&parsed_creators[0..end_index]);
             old_creators
         }
-    } else {
-        parsed_creators
+        _ => parsed_creators,
     };
 
-    let shares = new_creators.iter().fold(0, |acc, c| acc + c.share);
-    if shares != 100 {
-        return Err(anyhow!("Creators shares must sum to 100!"));
-    }
+    validate_creators(&new_creators).check()?;
 
-    let new_data = DataV2 {
-        creators: Some(new_creators),
-        seller_fee_basis_points: data_with_old_creators.seller_fee_basis_points,
-        name: data_with_old_creators.name,
-        symbol: data_with_old_creators.symbol,
-        uri: data_with_old_creators.uri,
-        collection: old_md.collection,
-        uses: old_md.uses,
-    };
-    let tx = update_data(client, &keypair, mint_account, new_data).await?;
-    Ok(tx)
+    Ok(new_creators)
 }
 
-pub async fn update_creator(
-    client: &RpcClient,
+#[derive(Debug, Clone)]
+pub enum CreatorEdit {
+    /// Removes a creator; its share must be given to others by a later edit.
+    Remove(Pubkey),
+    /// Swaps one address for another at the same position and share.
+    Replace {
+        old: Pubkey,
+        new: Pubkey,
+    },
+    /// Sets every creator's share, in creator order.
+    Rebalance(Vec<u8>),
+    SetShare {
+        index: usize,
+        share: u8,
+    },
+}
+
+/// Applies `edits` to the creators of a mint and updates it in one transaction.
+pub async fn edit_creators(
+    client: &dyn AccountSource,
     keypair: Keypair,
     mint_account: Pubkey,
-    new_creators: String,
-    should_append: bool,
+    edits: &[CreatorEdit],
 ) -> Result<Transaction, ActionError> {
-    let old_md = match decode(&client, &mint_account).await {
-        Ok(md) => md,
-        Err(e) => {
-            return Err(ActionError::ActionFailed(
-                mint_account.to_string(),
-                e.to_string(),
-            ))
-        }
+    let action_failed = |e: String| ActionError::ActionFailed(mint_account.to_string(), e);
+
+    let old_md = decode(client, &mint_account)
+        .await
+        .map_err(|e| action_failed(e.to_string()))?;
+
+    let patch = MetadataPatch {
+        creators: Some(
+            apply_creator_edits(
+                old_md.data.creators.clone().unwrap_or_default(),
+                edits,
+                &keypair.pubkey(),
+            )
+            .map_err(|e| action_failed(e.to_string()))?,
+        ),
+        ..Default::default()
     };
 
-    let data_with_old_creators = old_md.data;
-    let parsed_creators = match parse_cli_creators(new_creators, should_append) {
-        Ok(creators) => creators,
-        Err(e) => {
-            return Err(ActionError::ActionFailed(
-                mint_account.to_string(),
-                e.to_string(),
-            ))
-        }
+    update_metadata(client, &keypair, &old_md, &patch)
+        .await
+        .map_err(|e| action_failed(e.to_string()))
+}
+
+/// Applies `edits` in order and validates the result.
+///
+/// Verified creators other than `update_authority` can't be removed or replaced, since the
+/// program only lets a creator clear its own verification. Replacement addresses start
+/// unverified.
+pub fn apply_creator_edits(
+    mut creators: Vec<Creator>,
+    edits: &[CreatorEdit],
+    update_authority: &Pubkey,
+) -> AnyResult<Vec<Creator>> {
+    let position = |creators: &[Creator], address: &Pubkey| {
+        creators
+            .iter()
+            .position(|c| c.address == *address)
+            .ok_or_else(|| anyhow!("{} is not a creator", address))
     };
 
-    let new_creators = if let Some(mut old_creators) = data_with_old_creators.creators {
-        if !should_append {
-            parsed_creators
-        } else {
-            let remaining_space = 5 - old_creators.len();
-            warn!(
-                "Appending {} new creators with old creators with shares of 0",
-                parsed_creators.len()
-            );
-            let end_index = cmp::min(parsed_creators.len(), remaining_space);
-            old_creators.append(&mut parsed_creators[0..end_index].to_vec());
-            old_creators
+    for edit in edits {
+        match edit {
+            CreatorEdit::Remove(address) => {
+                let index = position(&creators, address)?;
+                check_unverify(&creators[index], update_authority)?;
+                creators.remove(index);
+            }
+            CreatorEdit::Replace { old, new } => {
+                let index = position(&creators, old)?;
+                check_unverify(&creators[index], update_authority)?;
+                creators[index] = Creator {
+                    address: *new,
+                    verified: false,
+                    share: creators[index].share,
+                };
+            }
+            CreatorEdit::Rebalance(shares) => {
+                if shares.len() != creators.len() {
+                    return Err(anyhow!(
+                        "Expected {} shares, got {}",
+                        creators.len(),
+                        shares.len()
+                    ));
+                }
+                for (creator, share) in creators.iter_mut().zip(shares) {
+                    creator.share = *share;
+                }
+            }
+            CreatorEdit::SetShare { index, share } => {
+                creators
+                    .get_mut(*index)
+                    .ok_or_else(|| anyhow!("No creator at index {}", index))?
+                    .share = *share;
+            }
         }
-    } else {
-        parsed_creators
-    };
+    }
+
+    validate_creators(&creators).check()?;
+
+    Ok(creators)
+}
 
-    let shares = new_creators.iter().fold(0, |acc, c| acc + c.share);
-    if shares != 100 {
-        return Err(ActionError::ActionFailed(
-            mint_account.to_string(),
-            "Creators shares must sum to 100!".to_string(),
+fn check_unverify(creator: &Creator, update_authority: &Pubkey) -> AnyResult<()> {
+    if creator.verified && creator.address != *update_authority {
+        return Err(anyhow!(
+            "{} is a verified creator and must remove its own verification first",
+            creator.address
         ));
     }
+    Ok(())
+}
 
-    let new_data = DataV2 {
-        creators: Some(new_creators),
-        seller_fee_basis_points: data_with_old_creators.seller_fee_basis_points,
-        name: data_with_old_creators.name,
-        symbol: data_with_old_creators.symbol,
-        uri: data_with_old_creators.uri,
-        collection: old_md.collection,
-        uses: old_md.uses,
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn creator(address: Pubkey, verified: bool, share: u8) -> Creator {
+        Creator {
+            address,
+            verified,
+            share,
+        }
+    }
+
+    #[test]
+    fn test_apply_creator_edits() {
+        let ua = Pubkey::new_unique();
+        let artist = Pubkey::new_unique();
+        let old_wallet = Pubkey::new_unique();
+        let new_wallet = Pubkey::new_unique();
+        let creators = vec![
+            creator(ua, true, 0),
+            creator(artist, true, 60),
+            creator(old_wallet, false, 40),
+        ];
+
+        // Wallet rotation keeps the share and the other verified flags.
+        let rotated = apply_creator_edits(
+            creators.clone(),
+            &[CreatorEdit::Replace {
+                old: old_wallet,
+                new: new_wallet,
+            }],
+            &ua,
+        )
+        .unwrap();
+        assert_eq!(rotated[2], creator(new_wallet, false, 40));
+        assert!(rotated[1].verified);
+
+        let removed = apply_creator_edits(
+            creators.clone(),
+            &[
+                CreatorEdit::Remove(old_wallet),
+                CreatorEdit::SetShare {
+                    index: 1,
+                    share: 100,
+                },
+            ],
+            &ua,
+        )
+        .unwrap();
+        assert_eq!(
+            removed,
+            vec![creator(ua, true, 0), creator(artist, true, 100)]
+        );
 
-    match update_data(&client, &keypair, &mint_account, new_data).await {
-        Ok(tx) => Ok(tx),
-        Err(e) => Err(ActionError::ActionFailed(
-            mint_account.to_string(),
-            e.to_string(),
-        )),
+        // Shares no longer total 100.
+        assert!(
+            apply_creator_edits(creators.clone(), &[CreatorEdit::Remove(old_wallet)], &ua).is_err()
+        );
+        // The artist must unverify themselves.
+        assert!(apply_creator_edits(
+            creators.clone(),
+            &[
+                CreatorEdit::Remove(artist),
+                CreatorEdit::Rebalance(vec![0, 100])
+            ],
+            &ua
+        )
+        .is_err());
+        assert!(apply_creator_edits(
+            creators,
+            &[CreatorEdit::Replace {
+                old: old_wallet,
+                new: artist
+            }],
+            &ua
+        )
+        .is_err());
     }
 }
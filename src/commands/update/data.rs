@@ -1,6 +1,5 @@
 use anyhow::Result;
 use mpl_token_metadata::{instruction::update_metadata_accounts_v2, state::DataV2};
-use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
     pubkey::Pubkey,
     signer::{keypair::Keypair, Signer},
@@ -8,20 +7,28 @@ use solana_sdk::{
 };
 use std::str::FromStr;
 
-use crate::commands::decode::get_metadata_pda;
+use crate::account_source::AccountSource;
 use crate::constants::*;
+use crate::derive::derive_metadata_pda;
+use crate::validate::validate_data_v2;
 
 pub async fn update_data(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     keypair: &Keypair,
     mint_account: &Pubkey,
     data: DataV2,
 ) -> Result<Transaction> {
     let program_id = Pubkey::from_str(METAPLEX_PROGRAM_ID)?;
-    let metadata_account = get_metadata_pda(mint_account);
+    let metadata_account = derive_metadata_pda(mint_account);
 
     let update_authority = keypair.pubkey();
 
+    let report = validate_data_v2(&data, Some(&update_authority));
+    for warning in &report.warnings {
+        log::warn!("{}: {}", mint_account, warning);
+    }
+    report.check()?;
+
     let ix = update_metadata_accounts_v2(
         program_id,
         metadata_account,
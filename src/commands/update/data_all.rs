@@ -0,0 +1,318 @@
+use futures::stream::{self, StreamExt};
+use indexmap::IndexMap;
+use mpl_token_metadata::state::Metadata;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::Write,
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+};
+
+use super::common::*;
+use crate::data::{NFTData, UpdateNFTData};
+use crate::errors::UpdateError;
+use crate::parse::convert_local_to_remote_data;
+
+pub struct UpdateDataAllArgs<'a> {
+    pub client: &'a dyn AccountSource,
+    pub keypair: Arc<Keypair>,
+    /// A directory of `<mint>.json` `NFTData` files, or a JSON file with an array of
+    /// `UpdateNFTData`.
+    pub path: PathBuf,
+    /// Failed mints are written here. If it already lists failures, only those are retried.
+    pub cache_file: Option<PathBuf>,
+    pub retries: u8,
+    pub batch_size: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct UpdateDataAllResults {
+    pub updated: Vec<String>,
+    pub unchanged: Vec<String>,
+    pub failed: UpdateCache,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UpdateCache(IndexMap<String, UpdateCacheItem>);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UpdateCacheItem {
+    pub error: Option<String>,
+}
+
+impl Deref for UpdateCache {
+    type Target = IndexMap<String, UpdateCacheItem>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for UpdateCache {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl UpdateCache {
+    pub fn new() -> Self {
+        UpdateCache(IndexMap::new())
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> AnyResult<Self> {
+        let file = fs::File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    pub fn write<W: Write>(&mut self, writer: W) -> AnyResult<()> {
+        self.sort_unstable_keys();
+        serde_json::to_writer_pretty(writer, &self)?;
+        Ok(())
+    }
+
+    pub fn update_errors(&mut self, errors: Vec<UpdateError>) {
+        // Clear out old errors.
+        self.clear();
+
+        for error in errors {
+            match error {
+                UpdateError::UpdateFailed(ref mint_address, _) => {
+                    let item = UpdateCacheItem {
+                        error: Some(error.to_string()),
+                    };
+
+                    self.insert(mint_address.to_string(), item);
+                }
+            }
+        }
+    }
+}
+
+enum UpdateOutcome {
+    Updated(String),
+    Unchanged(String),
+}
+
+/// Updates every mint whose on-chain data differs from its `NFTData`, retrying failures up
+/// to `args.retries` times.
+pub async fn update_data_all<'a>(args: &UpdateDataAllArgs<'a>) -> AnyResult<UpdateDataAllResults> {
+    let mut updates = read_update_data(&args.path)?;
+
+    if let Some(cache_file) = args.cache_file.as_ref().filter(|f| f.exists()) {
+        let cache = UpdateCache::read(cache_file)?;
+        if !cache.is_empty() {
+            info!("Retrying {} failed mints from the cache", cache.len());
+            updates.retain(|u| cache.contains_key(&u.mint_account));
+        }
+    }
+
+    let mut results = UpdateDataAllResults::default();
+    let mut counter = 0u8;
+
+    loop {
+        let outcomes: Vec<Result<UpdateOutcome, UpdateError>> = stream::iter(updates.iter())
+            .map(|update| update_one(args, update))
+            .buffer_unordered(args.batch_size.max(1))
+            .collect()
+            .await;
+
+        let mut failed = Vec::new();
+        for outcome in outcomes {
+            match outcome {
+                Ok(UpdateOutcome::Updated(mint)) => results.updated.push(mint),
+                Ok(UpdateOutcome::Unchanged(mint)) => results.unchanged.push(mint),
+                Err(e) => failed.push(e),
+            }
+        }
+        results.failed.update_errors(failed);
+
+        if results.failed.is_empty() || counter >= args.retries {
+            break;
+        }
+
+        counter += 1;
+        warn!(
+            "Retrying {} failed updates, attempt {}",
+            results.failed.len(),
+            counter
+        );
+        updates.retain(|u| results.failed.contains_key(&u.mint_account));
+    }
+
+    if let Some(cache_file) = &args.cache_file {
+        results.failed.write(fs::File::create(cache_file)?)?;
+    }
+
+    Ok(results)
+}
+
+async fn update_one<'a>(
+    args: &UpdateDataAllArgs<'a>,
+    update: &UpdateNFTData,
+) -> Result<UpdateOutcome, UpdateError> {
+    let failed = |e: String| UpdateError::UpdateFailed(update.mint_account.clone(), e);
+
+    let mint_account = Pubkey::from_str(&update.mint_account).map_err(|e| failed(e.to_string()))?;
+    let old_md = decode(args.client, &mint_account)
+        .await
+        .map_err(|e| failed(e.to_string()))?;
+
+    let patch = diff_nft_data(&old_md, &update.nft_data).map_err(|e| failed(e.to_string()))?;
+    if patch.is_empty() {
+        debug!("{} is unchanged", update.mint_account);
+        return Ok(UpdateOutcome::Unchanged(update.mint_account.clone()));
+    }
+
+    let tx = update_metadata(args.client, &args.keypair, &old_md, &patch)
+        .await
+        .map_err(|e| failed(e.to_string()))?;
+    args.client
+        .send_and_confirm_transaction(&tx)
+        .await
+        .map_err(|e| failed(e.to_string()))?;
+
+    info!("Updated {}", update.mint_account);
+    Ok(UpdateOutcome::Updated(update.mint_account.clone()))
+}
+
+/// Builds a patch of the fields in `data` that differ from the on-chain metadata.
+///
+/// Creators are compared by address and share. Verified flags are kept from chain, as
+/// only the creators themselves can change them. Without creators in `data`, the on-chain
+/// creators are kept.
+pub fn diff_nft_data(metadata: &Metadata, data: &NFTData) -> AnyResult<MetadataPatch> {
+    let new = convert_local_to_remote_data(data.clone())?;
+    let changed = |old: &str, new: &str| old.trim_end_matches(char::from(0)) != new;
+
+    let mut patch = MetadataPatch::default();
+
+    if changed(&metadata.data.name, &new.name) {
+        patch.name = Some(new.name);
+    }
+    if changed(&metadata.data.symbol, &new.symbol) {
+        patch.symbol = Some(new.symbol);
+    }
+    if changed(&metadata.data.uri, &new.uri) {
+        patch.uri = Some(new.uri);
+    }
+    if metadata.data.seller_fee_basis_points != new.seller_fee_basis_points {
+        patch.seller_fee_basis_points = Some(new.seller_fee_basis_points);
+    }
+
+    if let Some(new_creators) = new.creators {
+        let old_creators = metadata.data.creators.clone().unwrap_or_default();
+        let new_creators: Vec<Creator> = new_creators
+            .into_iter()
+            .map(|c| Creator {
+                verified: old_creators
+                    .iter()
+                    .any(|o| o.address == c.address && o.verified),
+                ..c
+            })
+            .collect();
+
+        let same_creators = old_creators.len() == new_creators.len()
+            && old_creators
+                .iter()
+                .zip(&new_creators)
+                .all(|(o, n)| o.address == n.address && o.share == n.share);
+        if !same_creators {
+            patch.creators = Some(new_creators);
+        }
+    }
+
+    Ok(patch)
+}
+
+/// Reads a directory of `<mint>.json` `NFTData` files or a file with an `UpdateNFTData` array.
+pub fn read_update_data(path: &Path) -> AnyResult<Vec<UpdateNFTData>> {
+    if !path.is_dir() {
+        let file = fs::File::open(path)?;
+        return serde_json::from_reader(file)
+            .map_err(|e| anyhow!("Invalid update data in {}: {}", path.display(), e));
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let mint_account = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .filter(|s| Pubkey::from_str(s).is_ok())
+                .ok_or_else(|| anyhow!("{} is not named <mint>.json", path.display()))?
+                .to_string();
+            let file = fs::File::open(&path)?;
+            let nft_data: NFTData = serde_json::from_reader(file)
+                .map_err(|e| anyhow!("Invalid NFT data in {}: {}", path.display(), e))?;
+
+            Ok(UpdateNFTData {
+                mint_account,
+                nft_data,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::NFTCreator;
+    use mpl_token_metadata::state::{Data, Key};
+
+    #[test]
+    fn test_diff_nft_data() {
+        let artist = Pubkey::new_unique();
+        let metadata = Metadata {
+            key: Key::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            data: Data {
+                name: format!("{:\0<32}", "Item #1"),
+                symbol: format!("{:\0<10}", "ITM"),
+                uri: format!("{:\0<200}", "https://example.com/1.json"),
+                seller_fee_basis_points: 500,
+                creators: Some(vec![Creator {
+                    address: artist,
+                    verified: true,
+                    share: 100,
+                }]),
+            },
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: None,
+            collection: None,
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        };
+        let mut data = NFTData {
+            name: "Item #1".to_string(),
+            symbol: "ITM".to_string(),
+            uri: "https://example.com/1.json".to_string(),
+            seller_fee_basis_points: 500,
+            creators: Some(vec![NFTCreator {
+                address: artist.to_string(),
+                verified: false,
+                share: 100,
+            }]),
+        };
+
+        assert!(diff_nft_data(&metadata, &data).unwrap().is_empty());
+
+        data.uri = "https://example.com/1-fixed.json".to_string();
+        let patch = diff_nft_data(&metadata, &data).unwrap();
+        assert_eq!(patch.uri, Some(data.uri.clone()));
+        assert!(patch.name.is_none() && patch.creators.is_none());
+
+        data.creators = None;
+        assert!(diff_nft_data(&metadata, &data).unwrap().creators.is_none());
+    }
+}
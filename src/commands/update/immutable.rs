@@ -1,7 +1,7 @@
 use super::common::*;
 
 pub struct SetImmutableArgs<'a> {
-    pub client: &'a RpcClient,
+    pub client: &'a dyn AccountSource,
     pub keypair: Arc<Keypair>,
     pub mint_account: Pubkey,
 }
@@ -9,7 +9,7 @@ pub struct SetImmutableArgs<'a> {
 pub async fn set_immutable<'a>(args: SetImmutableArgs<'a>) -> Result<Transaction, ActionError> {
     let mint_pubkey = args.mint_account;
     let update_authority = args.keypair.pubkey();
-    let metadata_account = get_metadata_pda(&mint_pubkey);
+    let metadata_account = derive_metadata_pda(&mint_pubkey);
 
     let ix = update_metadata_accounts_v2(
         TOKEN_METADATA_PROGRAM_ID,
@@ -20,3 +20,9 @@ pub use symbol::*;
 
 pub mod creator;
 pub use creator::*;
+
+pub mod patch;
+pub use patch::*;
+
+pub mod data_all;
+pub use data_all::*;
@@ -0,0 +1,165 @@
+use mpl_token_metadata::state::{Collection, Metadata, Uses};
+
+use super::{common::*, update_data};
+use crate::validate::validate_data_v2;
+
+/// Fields to change on a metadata account; `None` keeps the current value.
+#[derive(Debug, Clone, Default)]
+pub struct MetadataPatch {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub uri: Option<String>,
+    pub seller_fee_basis_points: Option<u16>,
+    pub creators: Option<Vec<Creator>>,
+    /// `Some(None)` removes the collection.
+    pub collection: Option<Option<Collection>>,
+    /// `Some(None)` removes the uses.
+    pub uses: Option<Option<Uses>>,
+}
+
+impl MetadataPatch {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.symbol.is_none()
+            && self.uri.is_none()
+            && self.seller_fee_basis_points.is_none()
+            && self.creators.is_none()
+            && self.collection.is_none()
+            && self.uses.is_none()
+    }
+
+    /// Merges the patch onto the current metadata.
+    pub fn apply(&self, metadata: &Metadata) -> AnyResult<DataV2> {
+        let data = DataV2 {
+            name: self
+                .name
+                .clone()
+                .unwrap_or_else(|| trim_padding(&metadata.data.name)),
+            symbol: self
+                .symbol
+                .clone()
+                .unwrap_or_else(|| trim_padding(&metadata.data.symbol)),
+            uri: self
+                .uri
+                .clone()
+                .unwrap_or_else(|| trim_padding(&metadata.data.uri)),
+            seller_fee_basis_points: self
+                .seller_fee_basis_points
+                .unwrap_or(metadata.data.seller_fee_basis_points),
+            creators: self
+                .creators
+                .clone()
+                .or_else(|| metadata.data.creators.clone()),
+            collection: self
+                .collection
+                .clone()
+                .unwrap_or_else(|| metadata.collection.clone()),
+            uses: self.uses.clone().unwrap_or_else(|| metadata.uses.clone()),
+        };
+
+        validate_data_v2(&data, None).check()?;
+
+        Ok(data)
+    }
+}
+
+/// Applies `patch` to the decoded `metadata` and builds the update transaction.
+///
+/// Taking the metadata that was already read keeps the update consistent with whatever the
+/// caller derived the patch from.
+pub async fn update_metadata(
+    client: &dyn AccountSource,
+    keypair: &Keypair,
+    metadata: &Metadata,
+    patch: &MetadataPatch,
+) -> AnyResult<Transaction> {
+    let new_data = patch.apply(metadata)?;
+
+    update_data(client, keypair, &metadata.mint, new_data).await
+}
+
+// On-chain strings are padded with null bytes up to their maximum length.
+fn trim_padding(s: &str) -> String {
+    s.trim_end_matches(char::from(0)).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mpl_token_metadata::state::{Data, Key};
+
+    #[test]
+    fn test_apply_patch() {
+        let metadata = Metadata {
+            key: Key::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            data: Data {
+                name: format!("{:\0<32}", "Old"),
+                symbol: "OLD".to_string(),
+                uri: "https://example.com/0.json".to_string(),
+                seller_fee_basis_points: 500,
+                creators: None,
+            },
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: None,
+            collection: Some(Collection {
+                verified: true,
+                key: Pubkey::new_unique(),
+            }),
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        };
+
+        let patch = MetadataPatch {
+            symbol: Some("NEW".to_string()),
+            seller_fee_basis_points: Some(250),
+            ..Default::default()
+        };
+        let data = patch.apply(&metadata).unwrap();
+        assert_eq!(data.name, "Old");
+        assert_eq!(data.symbol, "NEW");
+        assert_eq!(data.seller_fee_basis_points, 250);
+        assert_eq!(data.collection, metadata.collection);
+
+        let patch = MetadataPatch {
+            collection: Some(None),
+            ..Default::default()
+        };
+        assert_eq!(patch.apply(&metadata).unwrap().collection, None);
+
+        let patch = MetadataPatch {
+            symbol: Some("TOO_LONG_SYMBOL".to_string()),
+            ..Default::default()
+        };
+        assert!(patch.apply(&metadata).is_err());
+    }
+
+    #[test]
+    fn test_update_metadata_from_account_source() {
+        use crate::account_source::{fixtures::*, AccountSource};
+        use futures::executor::block_on;
+
+        let source = fixture_source();
+        let keypair = update_authority();
+        let patch = MetadataPatch {
+            name: Some("Fixture #2".to_string()),
+            ..Default::default()
+        };
+
+        let metadata = block_on(decode(&source, &MINT)).unwrap();
+        let tx = block_on(update_metadata(&source, &keypair, &metadata, &patch)).unwrap();
+        block_on(source.send_and_confirm_transaction(&tx)).unwrap();
+
+        let message = &source.sent_transactions()[0].message;
+        let accounts: Vec<Pubkey> = message.instructions[0]
+            .accounts
+            .iter()
+            .map(|i| message.account_keys[*i as usize])
+            .collect();
+        assert_eq!(accounts, vec![derive_metadata_pda(&MINT), keypair.pubkey()]);
+    }
+}
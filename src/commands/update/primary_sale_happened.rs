@@ -1,7 +1,7 @@
 use super::common::*;
 
 pub struct SetPrimarySaleHappenedArgs<'a> {
-    pub client: &'a RpcClient,
+    pub client: &'a dyn AccountSource,
     pub keypair: Arc<Keypair>,
     pub mint_account: Pubkey,
 }
@@ -11,7 +11,7 @@ pub async fn set_primary_sale_happened<'a>(
 ) -> Result<Transaction, ActionError> {
     let mint_pubkey = args.mint_account;
     let update_authority = args.keypair.pubkey();
-    let metadata_account = get_metadata_pda(&mint_pubkey);
+    let metadata_account = derive_metadata_pda(&mint_pubkey);
 
     let ix = update_metadata_accounts_v2(
         TOKEN_METADATA_PROGRAM_ID,
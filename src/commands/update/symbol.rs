@@ -1,29 +1,22 @@
-use super::{common::*, update_data};
+use super::common::*;
 
 pub async fn update_symbol(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     keypair: Keypair,
     mint_account: &Pubkey,
     new_symbol: &str,
 ) -> AnyResult<Transaction> {
-    let old_md = decode(&client, &mint_account)
-        .await
-        .map_err(|e| ActionError::ActionFailed(mint_account.to_string(), e.to_string()))?;
-    let data_with_old_symbol = old_md.data;
+    let action_failed = |e: String| ActionError::ActionFailed(mint_account.to_string(), e);
 
-    let new_data = DataV2 {
-        creators: data_with_old_symbol.creators,
-        seller_fee_basis_points: data_with_old_symbol.seller_fee_basis_points,
-        name: data_with_old_symbol.name,
-        symbol: new_symbol.to_owned(),
-        uri: data_with_old_symbol.uri,
-        collection: old_md.collection,
-        uses: old_md.uses,
+    let old_md = decode(client, mint_account)
+        .await
+        .map_err(|e| action_failed(e.to_string()))?;
+    let patch = MetadataPatch {
+        symbol: Some(new_symbol.to_owned()),
+        ..Default::default()
     };
 
-    let mint_account = mint_account;
-    let tx = update_data(&client, &keypair, &mint_account, new_data)
+    update_metadata(client, &keypair, &old_md, &patch)
         .await
-        .map_err(|e| ActionError::ActionFailed(mint_account.to_string(), e.to_string()))?;
-    Ok(tx)
+        .map_err(|e| action_failed(e.to_string()).into())
 }
@@ -1,7 +1,7 @@
 use super::common::*;
 
 pub struct SetUpdateAuthorityArgs<'a> {
-    pub client: &'a RpcClient,
+    pub client: &'a dyn AccountSource,
     pub keypair: Arc<Keypair>,
     pub payer: Arc<Keypair>,
     pub mint_account: Pubkey,
@@ -15,7 +15,7 @@ pub async fn set_update_authority<'a>(
     let update_authority = args.keypair.pubkey();
     let new_update_authority = args.new_authority;
 
-    let metadata_account = get_metadata_pda(mint_pubkey);
+    let metadata_account = derive_metadata_pda(mint_pubkey);
 
     let ix = update_metadata_accounts_v2(
         TOKEN_METADATA_PROGRAM_ID,
@@ -10,6 +10,8 @@ pub const ERROR_FILES_DIR: &str = ".error_files";
 
 pub const METAPLEX_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
 pub const CANDY_MACHINE_PROGRAM_ID: &str = "cndyAnrLdpjq1Ssp1z8xxDsB8dxe7u4HL5Nxi2K5WXZ";
+pub const CARDINAL_STAKE_POOL_PROGRAM_ID: &str = "stkBL96RZkjY5ine4TvPihGqW8UHJfch2cokjAPzV8i";
+pub const CARDINAL_TOKEN_MANAGER_PROGRAM_ID: &str = "mgr99QFMYByTqGPWmNqunV7vBLmWWXdSrHUfV8Jf3JM";
 
 pub const PUBLIC_RPC_URLS: &[&str] = &[
     "https://api.devnet.solana.com",
@@ -20,6 +22,9 @@ pub const PUBLIC_RPC_URLS: &[&str] = &[
 
 pub const DEFAULT_RPC_DELAY_MS: u32 = 200;
 
+// Maximum number of accounts the getMultipleAccounts RPC method accepts per request.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
 // This is a str so it can be used in Structopt arguments
 pub const DEFAULT_BATCH_SIZE: &str = "10";
 
@@ -8,7 +8,7 @@ pub struct NewUri {
     new_uri: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NFTData {
     pub name: String,
     pub symbol: String,
@@ -29,7 +29,7 @@ pub struct UpdateUriData {
     pub new_uri: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NFTCreator {
     pub address: String,
     pub verified: bool,
@@ -0,0 +1,187 @@
+use anyhow::{anyhow, Result};
+use solana_sdk::pubkey::Pubkey;
+use std::{collections::HashMap, fs, path::Path, str::FromStr, sync::RwLock};
+
+use crate::data::FoundError;
+use crate::parse::program_errors::{parse_idl, ProgramError};
+
+/// Error tables loaded at runtime, merged with the built-in `wtf_errors` maps by `find_errors`.
+static REGISTRY: RwLock<Vec<ErrorTable>> = RwLock::new(Vec::new());
+
+#[derive(Clone, Debug)]
+pub struct ErrorTable {
+    pub program_id: Pubkey,
+    pub domain: String,
+    pub errors: HashMap<String, String>,
+}
+
+impl ErrorTable {
+    pub fn new(program_id: Pubkey, domain: &str, errors: &[ProgramError]) -> Self {
+        let errors = errors
+            .iter()
+            .map(|e| (format!("{:X}", e.code), e.description()))
+            .collect();
+
+        Self {
+            program_id,
+            domain: domain.to_string(),
+            errors,
+        }
+    }
+}
+
+/// Adds a table to the registry, replacing any table already registered for the program.
+pub fn register_error_table(table: ErrorTable) {
+    let mut registry = REGISTRY.write().unwrap();
+    registry.retain(|t| t.program_id != table.program_id);
+    registry.push(table);
+}
+
+pub fn unregister_error_table(program_id: &Pubkey) {
+    REGISTRY
+        .write()
+        .unwrap()
+        .retain(|t| t.program_id != *program_id);
+}
+
+pub fn clear_error_tables() {
+    REGISTRY.write().unwrap().clear();
+}
+
+pub fn registered_programs() -> Vec<Pubkey> {
+    REGISTRY
+        .read()
+        .unwrap()
+        .iter()
+        .map(|t| t.program_id)
+        .collect()
+}
+
+/// Registers the `errors` of an Anchor IDL.
+///
+/// The program ID is taken from `program_id` if given, otherwise from the IDL's
+/// `metadata.address`.
+pub fn register_idl(contents: &str, program_id: Option<Pubkey>) -> Result<Pubkey> {
+    let idl = parse_idl(contents)?;
+
+    let program_id = match program_id {
+        Some(program_id) => program_id,
+        None => {
+            let address = idl
+                .metadata
+                .as_ref()
+                .and_then(|m| m.address.as_ref())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "IDL {} has no metadata.address, a program ID is required",
+                        idl.name
+                    )
+                })?;
+            Pubkey::from_str(address)?
+        }
+    };
+
+    let errors: Vec<ProgramError> = idl.errors.into_iter().map(ProgramError::from).collect();
+
+    register_error_table(ErrorTable::new(program_id, &idl.name, &errors));
+
+    Ok(program_id)
+}
+
+pub fn load_idl_file<P: AsRef<Path>>(path: P, program_id: Option<Pubkey>) -> Result<Pubkey> {
+    let contents = fs::read_to_string(path.as_ref())?;
+    register_idl(&contents, program_id)
+        .map_err(|e| anyhow!("Failed to load IDL {}: {}", path.as_ref().display(), e))
+}
+
+/// Loads every `.json` IDL in a directory.
+///
+/// IDLs without `metadata.address` may be named `<program_id>.json` instead.
+pub fn load_idl_dir<P: AsRef<Path>>(dir: P) -> Result<Vec<Pubkey>> {
+    let mut program_ids = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+
+        let program_id = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| Pubkey::from_str(s).ok());
+
+        program_ids.push(load_idl_file(&path, program_id)?);
+    }
+
+    Ok(program_ids)
+}
+
+/// Looks up a hex error code in the registered tables of all programs.
+pub fn find_registered_errors(hex_code: &str) -> Vec<FoundError> {
+    let hex_code = hex_code.to_uppercase();
+
+    REGISTRY
+        .read()
+        .unwrap()
+        .iter()
+        .filter_map(|t| {
+            t.errors.get(&hex_code).map(|message| FoundError {
+                domain: t.domain.clone(),
+                message: message.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Looks up a hex error code in the table registered for a single program.
+pub fn find_program_error(program_id: &Pubkey, hex_code: &str) -> Option<FoundError> {
+    let hex_code = hex_code.to_uppercase();
+
+    REGISTRY
+        .read()
+        .unwrap()
+        .iter()
+        .find(|t| t.program_id == *program_id)
+        .and_then(|t| {
+            t.errors.get(&hex_code).map(|message| FoundError {
+                domain: t.domain.clone(),
+                message: message.clone(),
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::find_errors;
+
+    #[test]
+    fn test_register_idl_errors() {
+        let idl = r#"{
+            "version": "0.1.0",
+            "name": "in_house_staking",
+            "instructions": [],
+            "errors": [
+                { "code": 6000, "name": "StakeLocked", "msg": "Stake is still locked" }
+            ]
+        }"#;
+        let program_id = Pubkey::new_unique();
+
+        assert!(register_idl(idl, None).is_err());
+        assert_eq!(register_idl(idl, Some(program_id)).unwrap(), program_id);
+
+        let found = find_program_error(&program_id, "1770").unwrap();
+        assert_eq!(found.domain, "in_house_staking");
+        assert_eq!(found.message, "StakeLocked: Stake is still locked");
+
+        // Merged with the built-in Candy Machine error using the same code.
+        let domains: Vec<String> = find_errors("1770").into_iter().map(|e| e.domain).collect();
+        assert!(domains.contains(&"Candy Machine".to_string()));
+        assert!(domains.contains(&"in_house_staking".to_string()));
+
+        unregister_error_table(&program_id);
+        assert!(find_program_error(&program_id, "1770").is_none());
+    }
+}
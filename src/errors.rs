@@ -4,20 +4,26 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum DecodeError {
-    #[error("no account data found")]
+    #[error("no account data found for {0}")]
     MissingAccount(String),
 
-    #[error("failed to get account data")]
+    #[error("failed to get account data: {0}")]
     ClientError(ClientErrorKind),
 
     #[error("network request failed after three attempts: ensure you used a valid address and check the state of the Solana cluster")]
     NetworkError(String),
 
-    #[error("failed to parse string into Pubkey")]
+    #[error("failed to parse {0} into Pubkey")]
     PubkeyParseFailed(String),
 
-    #[error("failed to decode metadata")]
+    #[error("failed to decode metadata: {0}")]
     DecodeMetadataFailed(String),
+
+    #[error("failed to fetch off-chain metadata: {0}")]
+    OffChainFetchFailed(String),
+
+    #[error("account is not a supported token metadata account: {0}")]
+    UnsupportedAccount(String),
 }
 
 pub type MintAddress = String;
@@ -29,6 +35,39 @@ pub enum MigrateError {
     MigrationFailed(MintAddress, NetworkError),
 }
 
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    #[error("name is {0} bytes, the maximum is {1}")]
+    NameTooLong(usize, usize),
+
+    #[error("symbol is {0} bytes, the maximum is {1}")]
+    SymbolTooLong(usize, usize),
+
+    #[error("uri is {0} bytes, the maximum is {1}")]
+    UriTooLong(usize, usize),
+
+    #[error("seller fee basis points must be at most 10000, got {0}")]
+    InvalidSellerFee(u16),
+
+    #[error("creators must not be empty")]
+    NoCreators,
+
+    #[error("there are {0} creators, the maximum is {1}")]
+    TooManyCreators(usize, usize),
+
+    #[error("creator shares must sum to 100, got {0}")]
+    InvalidShareTotal(u16),
+
+    #[error("duplicate creator address {0}")]
+    DuplicateCreator(String),
+
+    #[error("invalid creator address {0}")]
+    InvalidCreatorAddress(String),
+
+    #[error("update authority {0} is not a verified creator")]
+    UpdateAuthorityNotVerified(String),
+}
+
 #[derive(Error, Debug)]
 pub enum UpdateError {
     #[error("Action failed with error: {1}")]
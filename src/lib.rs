@@ -1,8 +1,12 @@
+pub mod account_source;
 pub mod commands;
 pub mod constants;
 pub mod data;
 pub mod derive;
+pub mod error_registry;
 pub mod errors;
+pub mod output;
 pub mod parse;
 pub mod utils;
+pub mod validate;
 pub mod wtf_errors;
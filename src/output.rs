@@ -0,0 +1,288 @@
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// An indented JSON array.
+    #[default]
+    Pretty,
+    /// A JSON array on a single line.
+    Compact,
+    /// One JSON object per line.
+    Ndjson,
+    /// A header row followed by one row per record. Nested values are written as JSON.
+    Csv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" | "pretty" => Ok(OutputFormat::Pretty),
+            "compact" => Ok(OutputFormat::Compact),
+            "ndjson" | "jsonl" => Ok(OutputFormat::Ndjson),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(format!("Invalid output format: {}", s)),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// Picks the format from a file extension, defaulting to pretty JSON.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("csv") => OutputFormat::Csv,
+            Some("ndjson") | Some("jsonl") => OutputFormat::Ndjson,
+            _ => OutputFormat::Pretty,
+        }
+    }
+}
+
+/// Writes records one at a time, so large outputs don't have to be held in memory.
+///
+/// CSV columns are taken from the first record; fields later records don't share with it
+/// are dropped. Records of varying shape should be flattened into a fixed struct first.
+pub struct RecordWriter<W: Write> {
+    sink: Sink<W>,
+    format: OutputFormat,
+    columns: Option<Vec<String>>,
+    count: usize,
+}
+
+enum Sink<W: Write> {
+    Json(W),
+    Csv(Box<csv::Writer<W>>),
+}
+
+impl<W: Write> RecordWriter<W> {
+    pub fn new(writer: W, format: OutputFormat) -> Self {
+        let sink = match format {
+            OutputFormat::Csv => Sink::Csv(Box::new(
+                csv::WriterBuilder::new()
+                    .has_headers(false)
+                    .from_writer(writer),
+            )),
+            _ => Sink::Json(writer),
+        };
+
+        Self {
+            sink,
+            format,
+            columns: None,
+            count: 0,
+        }
+    }
+
+    pub fn write<T: Serialize>(&mut self, record: &T) -> Result<()> {
+        match &mut self.sink {
+            Sink::Json(writer) => match self.format {
+                OutputFormat::Ndjson => {
+                    serde_json::to_writer(&mut *writer, record)?;
+                    writer.write_all(b"\n")?;
+                }
+                format => {
+                    let separator = match (self.count, format) {
+                        (0, OutputFormat::Pretty) => "[\n",
+                        (0, _) => "[",
+                        (_, OutputFormat::Pretty) => ",\n",
+                        _ => ",",
+                    };
+                    writer.write_all(separator.as_bytes())?;
+
+                    if format == OutputFormat::Pretty {
+                        serde_json::to_writer_pretty(&mut *writer, record)?;
+                    } else {
+                        serde_json::to_writer(&mut *writer, record)?;
+                    }
+                }
+            },
+            Sink::Csv(writer) => {
+                let row = csv_row(record)?;
+
+                if self.columns.is_none() {
+                    let columns: Vec<String> = row.keys().cloned().collect();
+                    writer.write_record(&columns)?;
+                    self.columns = Some(columns);
+                }
+
+                let values: Vec<String> = self
+                    .columns
+                    .iter()
+                    .flatten()
+                    .map(|column| row.get(column).cloned().unwrap_or_default())
+                    .collect();
+                writer.write_record(&values)?;
+            }
+        }
+
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Closes the JSON array and flushes the writer.
+    pub fn finish(self) -> Result<W> {
+        let mut writer = match self.sink {
+            Sink::Json(writer) => writer,
+            Sink::Csv(writer) => writer
+                .into_inner()
+                .map_err(|e| anyhow!("Failed to flush CSV output: {}", e.error()))?,
+        };
+
+        match (self.format, self.count) {
+            (OutputFormat::Pretty, 0) | (OutputFormat::Compact, 0) => writer.write_all(b"[]")?,
+            (OutputFormat::Pretty, _) => writer.write_all(b"\n]")?,
+            (OutputFormat::Compact, _) => writer.write_all(b"]")?,
+            _ => {}
+        }
+        writer.flush()?;
+
+        Ok(writer)
+    }
+}
+
+pub fn write_records<W: Write, T: Serialize>(
+    writer: W,
+    records: &[T],
+    format: OutputFormat,
+) -> Result<()> {
+    let mut writer = RecordWriter::new(writer, format);
+    for record in records {
+        writer.write(record)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+pub fn records_to_string<T: Serialize>(records: &[T], format: OutputFormat) -> Result<String> {
+    let mut buf = Vec::new();
+    write_records(&mut buf, records, format)?;
+    Ok(String::from_utf8(buf)?)
+}
+
+/// Writes `records` to `path`, choosing the format from its extension.
+pub fn write_records_to_file<P: AsRef<Path>, T: Serialize>(path: P, records: &[T]) -> Result<()> {
+    let format = OutputFormat::from_path(&path);
+    let file = File::create(path.as_ref())
+        .map_err(|e| anyhow!("Failed to create {}: {}", path.as_ref().display(), e))?;
+    write_records(BufWriter::new(file), records, format)
+}
+
+/// Resolves an output argument to a file: an existing directory gets `<name>.json` inside it,
+/// following the `<dir>/<prefix>_mint_accounts.json` convention, and anything else is used as
+/// the file path.
+pub fn output_file_path<P: AsRef<Path>>(output: P, name: &str) -> PathBuf {
+    let output = output.as_ref();
+
+    if output.is_dir() {
+        output.join(format!("{}.json", name))
+    } else {
+        output.to_path_buf()
+    }
+}
+
+// Top-level fields become columns in the order the record declares them; a record that
+// isn't an object is a single `value` column.
+fn csv_row<T: Serialize>(record: &T) -> Result<IndexMap<String, String>> {
+    let json = serde_json::to_string(record)?;
+
+    // A `Value` map would sort the fields, an `IndexMap` keeps them in serialization order.
+    if let Ok(fields) = serde_json::from_str::<IndexMap<String, Value>>(&json) {
+        return Ok(fields
+            .into_iter()
+            .map(|(key, value)| (key, csv_cell(&value)))
+            .collect());
+    }
+
+    let value: Value = serde_json::from_str(&json)?;
+    Ok(IndexMap::from([("value".to_string(), csv_cell(&value))]))
+}
+
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Bool(_) | Value::Number(_) => value.to_string(),
+        Value::Array(_) | Value::Object(_) => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Serialize)]
+    struct Record {
+        mint: &'static str,
+        supply: Option<u64>,
+        creators: Value,
+    }
+
+    #[test]
+    fn test_write_records() {
+        let records = vec![
+            Record {
+                mint: "A",
+                supply: Some(1),
+                creators: json!([{ "share": 100 }]),
+            },
+            Record {
+                mint: "B, C",
+                supply: None,
+                creators: json!([]),
+            },
+        ];
+
+        assert_eq!(
+            records_to_string(&records, OutputFormat::Compact).unwrap(),
+            r#"[{"mint":"A","supply":1,"creators":[{"share":100}]},{"mint":"B, C","supply":null,"creators":[]}]"#
+        );
+        assert_eq!(
+            records_to_string(&records, OutputFormat::Ndjson)
+                .unwrap()
+                .lines()
+                .count(),
+            2
+        );
+        assert_eq!(
+            records_to_string(&records, OutputFormat::Csv).unwrap(),
+            "mint,supply,creators\nA,1,\"[{\"\"share\"\":100}]\"\n\"B, C\",,[]\n"
+        );
+        assert_eq!(
+            records_to_string(&["A", "B"], OutputFormat::Csv).unwrap(),
+            "value\nA\nB\n"
+        );
+
+        let empty: Vec<Value> = Vec::new();
+        assert_eq!(
+            records_to_string(&empty, OutputFormat::Pretty).unwrap(),
+            "[]"
+        );
+        let pretty = records_to_string(&records, OutputFormat::Pretty).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&pretty).unwrap(),
+            serde_json::to_value(&records).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_output_file_path() {
+        let dir = std::env::temp_dir();
+        assert_eq!(
+            output_file_path(&dir, "A_mint_accounts"),
+            dir.join("A_mint_accounts.json")
+        );
+
+        let file = dir.join("mints.csv");
+        assert_eq!(output_file_path(&file, "A_mint_accounts"), file);
+    }
+}
@@ -8,6 +8,7 @@ use crate::data::{NFTCreator, NFTData};
 use crate::utils::find_errors;
 
 pub mod keypair;
+pub mod program_errors;
 pub mod solana_config;
 
 pub fn creator_is_verified(creators_opt: &Option<Vec<Creator>>, position: usize) -> bool {
@@ -0,0 +1,401 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::{fs, path::Path};
+use syn::{Attribute, Expr, ExprLit, Item, ItemEnum, Lit, Meta, NestedMeta};
+
+use crate::constants::ERROR_FILE_BEGIN;
+use crate::utils::generate_phf_map_var;
+
+/// First custom error code Anchor assigns to `#[error_code]` enums.
+pub const ANCHOR_CUSTOM_ERROR_OFFSET: u32 = 6000;
+
+/// First error code of Anchor's own `ErrorCode` enum.
+pub const ANCHOR_INTERNAL_ERROR_OFFSET: u32 = 100;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramError {
+    pub code: u32,
+    pub name: String,
+    pub msg: Option<String>,
+}
+
+impl ProgramError {
+    /// The value stored in the `wtf_errors.rs` tables: `Name: message` or just `Name`.
+    pub fn description(&self) -> String {
+        match self.msg {
+            Some(ref msg) => format!("{}: {}", self.name, msg),
+            None => self.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Idl {
+    pub name: String,
+    #[serde(default)]
+    pub errors: Vec<IdlError>,
+    pub metadata: Option<IdlMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdlError {
+    pub code: u32,
+    pub name: String,
+    pub msg: Option<String>,
+}
+
+impl From<IdlError> for ProgramError {
+    fn from(e: IdlError) -> Self {
+        Self {
+            code: e.code,
+            name: e.name,
+            msg: e.msg,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdlMetadata {
+    pub address: Option<String>,
+}
+
+pub fn parse_idl(contents: &str) -> Result<Idl> {
+    let idl: Idl = serde_json::from_str(contents)?;
+    Ok(idl)
+}
+
+pub fn parse_idl_errors(contents: &str) -> Result<Vec<ProgramError>> {
+    let idl = parse_idl(contents)?;
+
+    Ok(idl.errors.into_iter().map(ProgramError::from).collect())
+}
+
+/// Parses the error enum out of a Rust source file.
+///
+/// If `enum_name` is given that enum is used, otherwise the first enum carrying an
+/// Anchor `#[error_code]` attribute or `#[msg]`/`#[error]` variant attributes is picked.
+/// Variants without an explicit discriminant continue counting from the previous one,
+/// starting at `default_start`.
+pub fn parse_error_source(
+    contents: &str,
+    enum_name: Option<&str>,
+    default_start: u32,
+) -> Result<Vec<ProgramError>> {
+    let file = syn::parse_file(contents)?;
+
+    let enums: Vec<&ItemEnum> = file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Enum(item_enum) => Some(item_enum),
+            _ => None,
+        })
+        .collect();
+
+    let error_enum = match enum_name {
+        Some(name) => enums.iter().find(|e| e.ident == name),
+        None => enums.iter().find(|e| is_error_enum(e)),
+    }
+    .ok_or_else(|| anyhow!("Could not find Error enum"))?;
+
+    let mut errors = Vec::new();
+    let mut next_code = default_start;
+
+    for variant in error_enum.variants.iter() {
+        let code = match variant.discriminant {
+            Some((_, ref expr)) => parse_discriminant(expr)?,
+            None => next_code,
+        };
+
+        errors.push(ProgramError {
+            code,
+            name: variant.ident.to_string(),
+            msg: find_message(&variant.attrs)?,
+        });
+
+        next_code = code + 1;
+    }
+
+    Ok(errors)
+}
+
+/// Picks the starting code the same way the programs do: Anchor's own errors begin at 100,
+/// Anchor program errors at 6000 and plain `thiserror` enums at 0.
+pub fn default_error_start(file_name: &str, contents: &str) -> u32 {
+    if file_name.contains("anchor") {
+        ANCHOR_INTERNAL_ERROR_OFFSET
+    } else if contents.contains("#[error_code") || contents.contains("#[msg") {
+        ANCHOR_CUSTOM_ERROR_OFFSET
+    } else {
+        0
+    }
+}
+
+/// Converts an error file name such as `candy-error.rs` into the table name `CANDY_ERROR`.
+pub fn error_table_name(file_name: &str) -> String {
+    file_stem(file_name)
+        .split(['-', '_', ' '])
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_ascii_uppercase())
+        .collect::<Vec<String>>()
+        .join("_")
+}
+
+/// Converts an error file name such as `candy-error.rs` into the enum name `CandyError`.
+pub fn error_enum_name(file_name: &str) -> String {
+    file_stem(file_name)
+        .split(['-', '_', ' '])
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            let mut chars = s.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("")
+}
+
+/// Parses an error source file (`.rs`) or Anchor IDL (`.json`) into its errors.
+pub fn parse_error_file(file_name: &str, contents: &str) -> Result<Vec<ProgramError>> {
+    if file_name.ends_with(".json") {
+        return parse_idl_errors(contents);
+    }
+
+    let start = default_error_start(file_name, contents);
+
+    if file_name.contains("anchor") {
+        return parse_error_source(contents, Some("ErrorCode"), start);
+    }
+
+    let enum_name = error_enum_name(file_name);
+    match parse_error_source(contents, Some(&enum_name), start) {
+        Ok(errors) => Ok(errors),
+        Err(_) => parse_error_source(contents, None, start),
+    }
+}
+
+pub fn render_phf_map(var_name: &str, errors: &[ProgramError]) -> String {
+    let mut contents = generate_phf_map_var(var_name);
+
+    for error in errors {
+        contents.push_str(&format!(
+            "    \"{:X}\" => {:?},\n",
+            error.code,
+            error.description()
+        ));
+    }
+
+    contents.push_str("};\n\n");
+    contents
+}
+
+/// Generates the contents of `wtf_errors.rs` from a directory of error source files and IDLs.
+pub fn generate_wtf_errors<P: AsRef<Path>>(dir: P) -> Result<String> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort();
+
+    let mut contents = String::from(ERROR_FILE_BEGIN);
+
+    for path in paths {
+        let file_name = match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) if name.ends_with(".rs") || name.ends_with(".json") => name.to_string(),
+            _ => continue,
+        };
+
+        let file_contents = fs::read_to_string(&path)?;
+        let errors = parse_error_file(&file_name, &file_contents)
+            .map_err(|e| anyhow!("Failed to parse {}: {}", file_name, e))?;
+
+        contents.push_str(&render_phf_map(&error_table_name(&file_name), &errors));
+    }
+
+    Ok(contents)
+}
+
+fn file_stem(file_name: &str) -> &str {
+    let name = Path::new(file_name)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(file_name);
+
+    name.trim_end_matches(".rs").trim_end_matches(".json")
+}
+
+fn is_error_enum(item_enum: &ItemEnum) -> bool {
+    item_enum
+        .attrs
+        .iter()
+        .any(|attr| attr.path.is_ident("error_code"))
+        || item_enum.variants.iter().any(|v| {
+            v.attrs
+                .iter()
+                .any(|attr| attr.path.is_ident("msg") || attr.path.is_ident("error"))
+        })
+}
+
+fn parse_discriminant(expr: &Expr) -> Result<u32> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => Ok(lit.base10_parse::<u32>()?),
+        Expr::Paren(paren) => parse_discriminant(&paren.expr),
+        Expr::Group(group) => parse_discriminant(&group.expr),
+        _ => Err(anyhow!("Unsupported error discriminant expression")),
+    }
+}
+
+fn find_message(attrs: &[Attribute]) -> Result<Option<String>> {
+    for attr in attrs {
+        if !(attr.path.is_ident("msg") || attr.path.is_ident("error")) {
+            continue;
+        }
+
+        if let Meta::List(list) = attr.parse_meta()? {
+            if let Some(NestedMeta::Lit(Lit::Str(msg))) = list.nested.first() {
+                return Ok(Some(msg.value()));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANCHOR_PROGRAM_ERRORS: &str = r#"
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum CandyError {
+    /// Doc comment with "quotes" and a { brace.
+    #[msg("Account does not have correct owner!")]
+    IncorrectOwner,
+    #[msg(
+        "Can only provide up to 4 creators to candy machine (because candy machine is one)!"
+    )]
+    TooManyCreators,
+    // A plain comment.
+    NoMessage,
+    #[msg("Quoted \"string\" with a }")]
+    Quoted,
+}
+"#;
+
+    const THISERROR_PROGRAM_ERRORS: &str = r#"
+#[derive(Error, Clone, Debug, Eq, PartialEq, FromPrimitive)]
+pub enum MetadataError {
+    /// 0 Failed to unpack instruction data
+    #[error("Failed to unpack instruction data")]
+    InstructionUnpackError,
+
+    #[error("Failed to pack instruction data")]
+    InstructionPackError = 0x5,
+
+    #[error("Lamport balance below rent-exempt threshold")]
+    NotRentExempt,
+
+    #[error("Skipped ahead")]
+    SkippedAhead = (20),
+}
+
+pub enum Unrelated {
+    A,
+}
+"#;
+
+    const IDL: &str = r#"{
+    "version": "0.1.0",
+    "name": "my_program",
+    "instructions": [],
+    "errors": [
+        { "code": 6000, "name": "Overflow", "msg": "Math overflow" },
+        { "code": 6001, "name": "NoMsg" }
+    ],
+    "metadata": { "address": "cndy3Z4yapfJBmL3ShUp5exZKqR3z33thTzeNMm2gRZ" }
+}"#;
+
+    #[test]
+    fn test_parse_anchor_error_enum() {
+        let start = default_error_start("candy-error.rs", ANCHOR_PROGRAM_ERRORS);
+        let errors = parse_error_file("candy-error.rs", ANCHOR_PROGRAM_ERRORS).unwrap();
+
+        assert_eq!(start, 6000);
+        assert_eq!(
+            errors,
+            vec![
+                ProgramError {
+                    code: 6000,
+                    name: "IncorrectOwner".to_string(),
+                    msg: Some("Account does not have correct owner!".to_string()),
+                },
+                ProgramError {
+                    code: 6001,
+                    name: "TooManyCreators".to_string(),
+                    msg: Some("Can only provide up to 4 creators to candy machine (because candy machine is one)!".to_string()),
+                },
+                ProgramError {
+                    code: 6002,
+                    name: "NoMessage".to_string(),
+                    msg: None,
+                },
+                ProgramError {
+                    code: 6003,
+                    name: "Quoted".to_string(),
+                    msg: Some("Quoted \"string\" with a }".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_thiserror_enum_with_discriminants() {
+        let errors = parse_error_file("metadata-error.rs", THISERROR_PROGRAM_ERRORS).unwrap();
+        let codes: Vec<(u32, &str)> = errors.iter().map(|e| (e.code, e.name.as_str())).collect();
+
+        assert_eq!(
+            codes,
+            vec![
+                (0, "InstructionUnpackError"),
+                (5, "InstructionPackError"),
+                (6, "NotRentExempt"),
+                (20, "SkippedAhead"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_idl_errors() {
+        let errors = parse_error_file("my-program.json", IDL).unwrap();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].description(), "Overflow: Math overflow");
+        assert_eq!(errors[1].description(), "NoMsg");
+    }
+
+    #[test]
+    fn test_render_phf_map() {
+        let errors = parse_error_file("candy-error.rs", ANCHOR_PROGRAM_ERRORS).unwrap();
+        let rendered = render_phf_map(&error_table_name("candy-error.rs"), &errors);
+
+        assert!(rendered.starts_with(
+            "pub static CANDY_ERROR: phf::Map<&'static str, &'static str> = phf_map! {\n"
+        ));
+        assert!(rendered.contains(
+            "    \"1770\" => \"IncorrectOwner: Account does not have correct owner!\",\n"
+        ));
+        assert!(rendered.contains("    \"1772\" => \"NoMessage\",\n"));
+        assert!(rendered.contains("    \"1773\" => \"Quoted: Quoted \\\"string\\\" with a }\",\n"));
+        assert!(rendered.ends_with("};\n\n"));
+    }
+
+    #[test]
+    fn test_missing_error_enum() {
+        assert!(parse_error_source("pub struct NotAnEnum;", None, 0).is_err());
+    }
+}
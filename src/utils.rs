@@ -1,25 +1,30 @@
 use anyhow::{anyhow, Result};
-use serde::Deserialize;
-use serde_json::json;
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_client::rpc_request::RpcRequest;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
-use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::{
-    instruction::Instruction, signature::Keypair, signer::Signer, transaction::Transaction,
+    instruction::Instruction, message::Message, packet::PACKET_DATA_SIZE, signature::Keypair,
+    signer::Signer, system_instruction, transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+use spl_token::{
+    instruction::{initialize_mint, mint_to},
+    state::{Account, Mint},
 };
-use spl_token::state::Account;
 use std::str::FromStr;
-use std::{ops::Add, sync::Arc};
+use std::sync::Arc;
 
+use crate::account_source::AccountSource;
 use crate::data::FoundError;
+use crate::error_registry::find_registered_errors;
+use crate::parse::program_errors::{error_table_name, parse_error_file, render_phf_map};
 use crate::wtf_errors::{
     ANCHOR_ERROR, AUCTIONEER_ERROR, AUCTION_HOUSE_ERROR, CANDY_ERROR, METADATA_ERROR,
 };
 
 pub async fn send_and_confirm_transaction(
-    client: &RpcClient,
+    client: &dyn AccountSource,
     keypair: Keypair,
     instructions: &[Instruction],
 ) -> Result<(String, Transaction)> {
@@ -46,7 +51,7 @@ pub async fn send_and_confirm_transaction(
 }
 
 pub async fn async_send_and_confirm_transaction(
-    async_client: &RpcClient,
+    async_client: &dyn AccountSource,
     keypair: Arc<Keypair>,
     instructions: &[Instruction],
 ) -> Result<(String, Transaction)> {
@@ -63,120 +68,87 @@ pub async fn async_send_and_confirm_transaction(
     Ok((sig.to_string(), tx))
 }
 
+/// Sends a transaction signed by all `signers`; the first signer pays the fees.
+pub async fn send_and_confirm_transaction_with_signers(
+    client: &dyn AccountSource,
+    signers: &[&Keypair],
+    instructions: &[Instruction],
+) -> Result<(String, Transaction)> {
+    let payer = signers
+        .first()
+        .ok_or_else(|| anyhow!("At least one signer is required"))?;
+
+    let recent_blockhash = client.get_latest_blockhash().await?;
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        signers,
+        recent_blockhash,
+    );
+
+    let sig = client.send_and_confirm_transaction(&tx).await?;
+
+    log::debug!("TxId: {}", sig);
+    Ok((sig.to_string(), tx))
+}
+
+/// Fails if a legacy transaction of `instructions` would exceed the packet size limit.
+pub fn check_transaction_size(instructions: &[Instruction], payer: &Pubkey) -> Result<()> {
+    let message = Message::new(instructions, Some(payer));
+    let num_signatures = message.header.num_required_signatures as usize;
+    // Compact-u16 length prefix of the signatures, which is one byte below 128 signatures.
+    let size = 1 + num_signatures * 64 + message.serialize().len();
+
+    if size > PACKET_DATA_SIZE {
+        return Err(anyhow!(
+            "Transaction is {} bytes, over the {} byte limit",
+            size,
+            PACKET_DATA_SIZE
+        ));
+    }
+
+    Ok(())
+}
+
+/// Instructions creating a new NFT mint with `authority` as mint and freeze authority and
+/// minting its single token into the associated token account of `receiver`.
+pub async fn create_nft_mint_instructions(
+    client: &dyn AccountSource,
+    payer: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    receiver: &Pubkey,
+) -> Result<Vec<Instruction>> {
+    let rent = client
+        .get_minimum_balance_for_rent_exemption(Mint::LEN)
+        .await?;
+    let receiver_token_account = get_associated_token_address(receiver, mint);
+
+    let instructions = vec![
+        system_instruction::create_account(payer, mint, rent, Mint::LEN as u64, &spl_token::id()),
+        initialize_mint(&spl_token::id(), mint, authority, Some(authority), 0)?,
+        create_associated_token_account(payer, receiver, mint, &spl_token::id()),
+        mint_to(
+            &spl_token::id(),
+            mint,
+            &receiver_token_account,
+            authority,
+            &[],
+            1,
+        )?,
+    ];
+
+    Ok(instructions)
+}
+
 pub fn generate_phf_map_var(var_name: &str) -> String {
     format!("pub static {var_name}: phf::Map<&'static str, &'static str> = phf_map! {{\n")
 }
 
 pub fn convert_to_wtf_error(file_name: &str, file_contents: &str) -> Result<String> {
-    let file_names = file_name.replace(".rs", "").replace('-', " ");
-    let file_names_split = file_names.split(' ');
-
-    let file_name_capitalized = file_names_split
-        .clone()
-        .into_iter()
-        .map(|s| s.to_ascii_uppercase())
-        .collect::<Vec<String>>()
-        .join("_");
-
-    let mut error_contents = generate_phf_map_var(&file_name_capitalized);
-
-    let is_anchor = file_name.contains("anchor");
-
-    let mut starting_error_number: i64 = match is_anchor {
-        true => 100,
-        false => match file_contents.contains("#[msg") {
-            true => 6000,
-            false => 0,
-        },
-    };
-
-    let enum_name = if is_anchor {
-        String::from("ErrorCode")
-    } else {
-        file_names_split
-            .into_iter()
-            .map(|s| {
-                format!(
-                    "{}{}",
-                    s.get(0..1).unwrap().to_ascii_uppercase(),
-                    s.get(1..).unwrap()
-                )
-            })
-            .collect::<Vec<String>>()
-            .join("")
-    };
-
-    let error_index = match file_contents.find(&enum_name) {
-        Some(index) => index,
-        None => return Err(anyhow!("Could not find Error enum")),
-    };
-
-    let trimmed_content = match file_contents.get(error_index.add(enum_name.len() + 2)..) {
-        Some(contents) => contents.trim(),
-        None => return Err(anyhow!("Malformed Error enum")),
-    };
-
-    let error_lines = match trimmed_content.contains('}') {
-        true => trimmed_content.lines(),
-        false => return Err(anyhow!("Malformed Error enum")),
-    };
-
-    let mut parsed_error_line = String::from("\",\n");
-
-    for error_line in error_lines {
-        let error_line = error_line.trim();
-
-        if error_line.starts_with('}') {
-            break;
-        }
-
-        if error_line.starts_with('/') || error_line.is_empty() {
-            continue;
-        } else if !error_line.starts_with("#[")
-            && !error_line.starts_with('\"')
-            && !error_line.ends_with('\"')
-            && !error_line.ends_with(")]")
-        {
-            let enum_end_index = match error_line.find(',') {
-                Some(index) => index,
-                None => return Err(anyhow!("Malformed Error enum")),
-            };
-
-            let mut error_enum = match error_line.get(..enum_end_index) {
-                Some(res) => res,
-                None => return Err(anyhow!("Cannot parse Error enum")),
-            };
-
-            if error_enum.contains('=') {
-                let error_code_combo = error_enum.split('=').collect::<Vec<&str>>();
-
-                error_enum = error_code_combo[0].trim();
-                starting_error_number = error_code_combo[1].trim().parse::<i64>()?;
-            }
-
-            parsed_error_line = format!(
-                "    \"{:X}\" => \"{}{}",
-                starting_error_number, error_enum, parsed_error_line
-            );
-        } else if error_line.starts_with("#[") && error_line.ends_with(")]") {
-            let parsed_message = error_line
-                .replace("#[", "")
-                .replace("error(\"", "")
-                .replace("msg(\"", "")
-                .replace("\")]", "");
-
-            parsed_error_line = format!(": {}\",\n", parsed_message);
-        }
-
-        if parsed_error_line.contains("=>") {
-            error_contents.push_str(&parsed_error_line);
-            starting_error_number += 1;
-            parsed_error_line = String::from("\",\n");
-        }
-    }
+    let errors = parse_error_file(file_name, file_contents)?;
 
-    error_contents.push_str("};\n\n");
-    Ok(error_contents)
+    Ok(render_phf_map(&error_table_name(file_name), &errors))
 }
 
 pub fn find_errors(hex_code: &str) -> Vec<FoundError> {
@@ -218,6 +190,8 @@ pub fn find_errors(hex_code: &str) -> Vec<FoundError> {
         });
     }
 
+    found_errors.extend(find_registered_errors(&hex_code));
+
     found_errors
 }
 
@@ -225,17 +199,15 @@ pub fn clone_keypair(keypair: &Keypair) -> Keypair {
     Keypair::from_bytes(&keypair.to_bytes()).unwrap()
 }
 
-pub async fn get_largest_token_account_owner(client: &RpcClient, mint: Pubkey) -> Result<Pubkey> {
-    let request = RpcRequest::Custom {
-        method: "getTokenLargestAccounts",
-    };
-    let params = json!([mint.to_string(), { "commitment": "confirmed" }]);
-    let result: JRpcResponse = client.send(request, params).await?;
-
-    let token_accounts: Vec<TokenAccount> = result
-        .value
+pub async fn get_largest_token_account_owner(
+    client: &dyn AccountSource,
+    mint: Pubkey,
+) -> Result<Pubkey> {
+    let token_accounts: Vec<_> = client
+        .get_token_largest_accounts(&mint)
+        .await?
         .into_iter()
-        .filter(|account| account.amount.parse::<u64>().unwrap() == 1)
+        .filter(|balance| balance.amount.amount == "1")
         .collect();
 
     if token_accounts.len() > 1 {
@@ -255,27 +227,10 @@ pub async fn get_largest_token_account_owner(client: &RpcClient, mint: Pubkey) -
     let token_account = Pubkey::from_str(&token_accounts[0].address)?;
 
     let account = client
-        .get_account_with_commitment(&token_account, CommitmentConfig::confirmed())
+        .get_account(&token_account)
         .await?
-        .value
-        .unwrap();
+        .ok_or_else(|| anyhow!("Token account {} not found", token_account))?;
     let account_data = Account::unpack(&account.data)?;
 
     Ok(account_data.owner)
 }
-
-#[derive(Debug, Deserialize)]
-pub struct JRpcResponse {
-    value: Vec<TokenAccount>,
-}
-
-#[derive(Debug, Deserialize)]
-struct TokenAccount {
-    address: String,
-    amount: String,
-    // decimals: u8,
-    // #[serde(rename = "uiAmount")]
-    // ui_amount: f32,
-    // #[serde(rename = "uiAmountString")]
-    // ui_amount_string: String,
-}
@@ -0,0 +1,291 @@
+use anyhow::{anyhow, Result};
+use mpl_token_metadata::state::{Creator, DataV2, MAX_CREATOR_LIMIT};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::{fs::File, path::Path, str::FromStr};
+
+use crate::constants::{MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH};
+use crate::data::{NFTCreator, NFTData, UpdateNFTData};
+use crate::errors::ValidationError;
+
+const MAX_SELLER_FEE_BASIS_POINTS: u16 = 10000;
+
+/// Problems found in metadata. Errors would make the transaction fail on chain; warnings
+/// are valid but probably unintended.
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub errors: Vec<ValidationError>,
+    pub warnings: Vec<ValidationError>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn check(&self) -> Result<()> {
+        if self.is_valid() {
+            return Ok(());
+        }
+
+        let errors: Vec<String> = self.errors.iter().map(|e| e.to_string()).collect();
+        Err(anyhow!("Invalid metadata: {}", errors.join(", ")))
+    }
+}
+
+/// Validates `data` against the token metadata program's limits.
+///
+/// If `update_authority` is given, it is expected to be a verified creator.
+pub fn validate_data_v2(data: &DataV2, update_authority: Option<&Pubkey>) -> ValidationReport {
+    let creators = data.creators.as_deref().map(to_nft_creators);
+
+    validate_fields(
+        &data.name,
+        &data.symbol,
+        &data.uri,
+        data.seller_fee_basis_points,
+        creators.as_deref(),
+        update_authority,
+    )
+}
+
+pub fn validate_nft_data(data: &NFTData, update_authority: Option<&Pubkey>) -> ValidationReport {
+    validate_fields(
+        &data.name,
+        &data.symbol,
+        &data.uri,
+        data.seller_fee_basis_points,
+        data.creators.as_deref(),
+        update_authority,
+    )
+}
+
+fn validate_fields(
+    name: &str,
+    symbol: &str,
+    uri: &str,
+    seller_fee_basis_points: u16,
+    creators: Option<&[NFTCreator]>,
+    update_authority: Option<&Pubkey>,
+) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    // On-chain strings are padded with null bytes, which don't count towards the limit.
+    let trimmed_len = |s: &str| s.trim_end_matches(char::from(0)).len();
+
+    if trimmed_len(name) > MAX_NAME_LENGTH {
+        report.errors.push(ValidationError::NameTooLong(
+            trimmed_len(name),
+            MAX_NAME_LENGTH,
+        ));
+    }
+    if trimmed_len(symbol) > MAX_SYMBOL_LENGTH {
+        report.errors.push(ValidationError::SymbolTooLong(
+            trimmed_len(symbol),
+            MAX_SYMBOL_LENGTH,
+        ));
+    }
+    if trimmed_len(uri) > MAX_URI_LENGTH {
+        report.errors.push(ValidationError::UriTooLong(
+            trimmed_len(uri),
+            MAX_URI_LENGTH,
+        ));
+    }
+    if seller_fee_basis_points > MAX_SELLER_FEE_BASIS_POINTS {
+        report
+            .errors
+            .push(ValidationError::InvalidSellerFee(seller_fee_basis_points));
+    }
+
+    if let Some(creators) = creators {
+        validate_creator_list(&mut report, creators, update_authority);
+    }
+
+    report
+}
+
+/// Validates a creators array on its own, e.g. after editing the creators of a mint.
+pub fn validate_creators(creators: &[Creator]) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    validate_creator_list(&mut report, &to_nft_creators(creators), None);
+    report
+}
+
+fn validate_creator_list(
+    report: &mut ValidationReport,
+    creators: &[NFTCreator],
+    update_authority: Option<&Pubkey>,
+) {
+    if creators.is_empty() {
+        report.errors.push(ValidationError::NoCreators);
+        return;
+    }
+    if creators.len() > MAX_CREATOR_LIMIT {
+        report.errors.push(ValidationError::TooManyCreators(
+            creators.len(),
+            MAX_CREATOR_LIMIT,
+        ));
+    }
+
+    let share_total = creators.iter().map(|c| c.share as u16).sum::<u16>();
+    if share_total != 100 {
+        report
+            .errors
+            .push(ValidationError::InvalidShareTotal(share_total));
+    }
+
+    for (i, creator) in creators.iter().enumerate() {
+        if Pubkey::from_str(&creator.address).is_err() {
+            report.errors.push(ValidationError::InvalidCreatorAddress(
+                creator.address.clone(),
+            ));
+        }
+        if creators[..i].iter().any(|c| c.address == creator.address) {
+            report
+                .errors
+                .push(ValidationError::DuplicateCreator(creator.address.clone()));
+        }
+    }
+
+    if let Some(update_authority) = update_authority {
+        let update_authority = update_authority.to_string();
+        if !creators
+            .iter()
+            .any(|c| c.verified && c.address == update_authority)
+        {
+            report
+                .warnings
+                .push(ValidationError::UpdateAuthorityNotVerified(
+                    update_authority,
+                ));
+        }
+    }
+}
+
+fn to_nft_creators(creators: &[Creator]) -> Vec<NFTCreator> {
+    creators
+        .iter()
+        .map(|c| NFTCreator {
+            address: c.address.to_string(),
+            verified: c.verified,
+            share: c.share,
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct LintResult {
+    /// The mint account for update data, otherwise the position in the file.
+    pub entry: String,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NFTDataFile {
+    Updates(Vec<UpdateNFTData>),
+    Many(Vec<NFTData>),
+    One(NFTData),
+}
+
+/// Lints a JSON file holding one `NFTData`, an array of them, or an array of `UpdateNFTData`.
+/// Only entries with problems are returned.
+pub fn lint_nft_data_file<P: AsRef<Path>>(
+    path: P,
+    update_authority: Option<&Pubkey>,
+) -> Result<Vec<LintResult>> {
+    let file = File::open(path.as_ref())?;
+    let contents: NFTDataFile = serde_json::from_reader(file).map_err(|e| {
+        anyhow!(
+            "{} is not NFT data or update data: {}",
+            path.as_ref().display(),
+            e
+        )
+    })?;
+
+    let entries: Vec<(String, NFTData)> = match contents {
+        NFTDataFile::Updates(updates) => updates
+            .into_iter()
+            .map(|u| (u.mint_account, u.nft_data))
+            .collect(),
+        NFTDataFile::Many(many) => many
+            .into_iter()
+            .enumerate()
+            .map(|(i, d)| (i.to_string(), d))
+            .collect(),
+        NFTDataFile::One(one) => vec![("0".to_string(), one)],
+    };
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|(entry, data)| {
+            let report = validate_nft_data(&data, update_authority);
+            if report.errors.is_empty() && report.warnings.is_empty() {
+                return None;
+            }
+
+            Some(LintResult {
+                entry,
+                errors: report.errors.iter().map(|e| e.to_string()).collect(),
+                warnings: report.warnings.iter().map(|e| e.to_string()).collect(),
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_nft_data() {
+        let update_authority = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let mut data = NFTData {
+            name: "A".repeat(40),
+            symbol: "SYM".to_string(),
+            uri: "https://example.com/0.json".to_string(),
+            seller_fee_basis_points: 10001,
+            creators: Some(vec![
+                NFTCreator {
+                    address: other.to_string(),
+                    verified: false,
+                    share: 60,
+                },
+                NFTCreator {
+                    address: other.to_string(),
+                    verified: false,
+                    share: 30,
+                },
+            ]),
+        };
+
+        let report = validate_nft_data(&data, Some(&update_authority));
+        assert_eq!(
+            report.errors,
+            vec![
+                ValidationError::NameTooLong(40, MAX_NAME_LENGTH),
+                ValidationError::InvalidSellerFee(10001),
+                ValidationError::InvalidShareTotal(90),
+                ValidationError::DuplicateCreator(other.to_string()),
+            ]
+        );
+        assert_eq!(
+            report.warnings,
+            vec![ValidationError::UpdateAuthorityNotVerified(
+                update_authority.to_string()
+            )]
+        );
+
+        data.name = "A".to_string();
+        data.seller_fee_basis_points = 500;
+        data.creators = Some(vec![NFTCreator {
+            address: update_authority.to_string(),
+            verified: true,
+            share: 100,
+        }]);
+        let report = validate_nft_data(&data, Some(&update_authority));
+        assert!(report.errors.is_empty() && report.warnings.is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+//! Shared harness for the integration tests: a `solana-test-validator` with the token metadata
+//! program loaded from `tests/fixtures/programs`.
+//!
+//! The validator binary is taken from `SOLANA_TEST_VALIDATOR` or the `PATH`. The tests are
+//! `#[ignore]`d because neither it nor the program fixture ship with the crate; run them with
+//! `cargo test -- --ignored`. `TestValidator::start` panics when either is missing.
+
+use metaboss_utils::{
+    commands::mint::{mint_nft, MintNftArgs},
+    data::{NFTCreator, NFTData},
+};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::{write_keypair_file, Keypair},
+    signer::Signer,
+};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    str::FromStr,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+pub const TOKEN_METADATA_SO: &str = "tests/fixtures/programs/mpl_token_metadata.so";
+
+const RPC_PORT: u16 = 18899;
+const FAUCET_PORT: u16 = 19900;
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The validators of all tests share ports, so only one runs at a time.
+static VALIDATOR_LOCK: Mutex<()> = Mutex::new(());
+
+pub struct TestValidator {
+    pub client: RpcClient,
+    /// Funded at genesis and used as update authority, creator and fee payer.
+    pub payer: Arc<Keypair>,
+    pub payer_path: String,
+    process: Child,
+    ledger: PathBuf,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TestValidator {
+    pub async fn start() -> Self {
+        let program = Path::new(env!("CARGO_MANIFEST_DIR")).join(TOKEN_METADATA_SO);
+        assert!(
+            program.exists(),
+            "{} not found, see tests/fixtures/programs/README.md",
+            program.display()
+        );
+
+        let validator =
+            env::var("SOLANA_TEST_VALIDATOR").unwrap_or_else(|_| "solana-test-validator".into());
+        let mut version = assert_cmd::Command::new(&validator);
+        version.arg("--version");
+        if let Err(err) = version.ok() {
+            panic!(
+                "{} --version failed, install it or set SOLANA_TEST_VALIDATOR: {}",
+                validator, err
+            );
+        }
+
+        let lock = VALIDATOR_LOCK
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let ledger = env::temp_dir().join(format!("metaboss-test-ledger-{}", Pubkey::new_unique()));
+        fs::create_dir_all(&ledger).unwrap();
+
+        let payer = Keypair::new();
+        let payer_path = ledger.join("payer.json").display().to_string();
+        write_keypair_file(&payer, &payer_path).unwrap();
+
+        let process = Command::new(&validator)
+            .arg("--reset")
+            .arg("--quiet")
+            .arg("--ledger")
+            .arg(&ledger)
+            .arg("--mint")
+            .arg(payer.pubkey().to_string())
+            .arg("--rpc-port")
+            .arg(RPC_PORT.to_string())
+            .arg("--faucet-port")
+            .arg(FAUCET_PORT.to_string())
+            .arg("--bpf-program")
+            .arg(mpl_token_metadata::ID.to_string())
+            .arg(&program)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to start solana-test-validator");
+
+        let mut validator = Self {
+            client: RpcClient::new_with_commitment(
+                format!("http://127.0.0.1:{}", RPC_PORT),
+                CommitmentConfig::confirmed(),
+            ),
+            payer: Arc::new(payer),
+            payer_path,
+            process,
+            ledger,
+            _lock: lock,
+        };
+        validator.wait_until_ready().await;
+
+        validator
+    }
+
+    async fn wait_until_ready(&mut self) {
+        let started = Instant::now();
+
+        loop {
+            if let Ok(Some(status)) = self.process.try_wait() {
+                panic!("solana-test-validator exited during startup: {}", status);
+            }
+
+            let ready = self.client.get_latest_blockhash().await.is_ok()
+                && self
+                    .client
+                    .get_balance(&self.payer.pubkey())
+                    .await
+                    .unwrap_or(0)
+                    > 0;
+            if ready {
+                return;
+            }
+
+            assert!(
+                started.elapsed() < STARTUP_TIMEOUT,
+                "solana-test-validator did not start within {:?}",
+                STARTUP_TIMEOUT
+            );
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+
+    /// Mints a one of one NFT with the payer as verified sole creator.
+    pub async fn mint_fixture_nft(&self, name: &str, collection_size: Option<u64>) -> Pubkey {
+        let args = MintNftArgs {
+            client: &self.client,
+            keypair: self.payer.clone(),
+            receiver: None,
+            nft_data: fixture_nft_data(name, &self.payer.pubkey()),
+            immutable: false,
+            primary_sale_happened: false,
+            max_editions: Some(0),
+            collection_mint: None,
+            verify_creator: true,
+            collection_size,
+        };
+
+        let minted = mint_nft(&args).await.expect("Failed to mint fixture NFT");
+        Pubkey::from_str(&minted.mint).unwrap()
+    }
+}
+
+impl Drop for TestValidator {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        let _ = fs::remove_dir_all(&self.ledger);
+    }
+}
+
+pub fn fixture_nft_data(name: &str, creator: &Pubkey) -> NFTData {
+    NFTData {
+        name: name.to_string(),
+        symbol: "FIX".to_string(),
+        uri: format!("https://example.com/fixture/{}.json", name),
+        seller_fee_basis_points: 500,
+        creators: Some(vec![NFTCreator {
+            address: creator.to_string(),
+            verified: false,
+            share: 100,
+        }]),
+    }
+}
@@ -0,0 +1,215 @@
+//! End-to-end tests against the token metadata program on a local test validator.
+//!
+//! These catch instructions built with accounts in the wrong order, which the offline unit
+//! tests can't. They are ignored by default; see `tests/common` for what they need.
+
+mod common;
+
+use metaboss_utils::{
+    account_source::AccountSource,
+    commands::{
+        burn::{burn, BurnArgs},
+        collections::{
+            count_verified_items, migrate_collection, set_and_verify_nft_collection,
+            unverify_nft_collection, MigrateArgs,
+        },
+        decode::decode,
+        snapshot::{
+            get_mint_accounts, snapshot_holders, snapshot_metadata, SnapshotHoldersArgs,
+            SnapshotMetadataArgs,
+        },
+        update::{update_metadata, MetadataPatch},
+    },
+    derive::derive_metadata_pda,
+    errors::DecodeError,
+};
+use mpl_token_metadata::state::CollectionDetails;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use spl_associated_token_account::get_associated_token_address;
+use std::time::Duration;
+
+use common::TestValidator;
+
+#[tokio::test]
+#[ignore = "needs solana-test-validator and tests/fixtures/programs/mpl_token_metadata.so"]
+async fn test_update_and_snapshot() {
+    let validator = TestValidator::start().await;
+    let client = &validator.client;
+    let mint = validator.mint_fixture_nft("Fixture #1", None).await;
+
+    let patch = MetadataPatch {
+        name: Some("Fixture #2".to_string()),
+        seller_fee_basis_points: Some(250),
+        ..Default::default()
+    };
+    let metadata = decode(client, &mint).await.unwrap();
+    let tx = update_metadata(client, &validator.payer, &metadata, &patch)
+        .await
+        .unwrap();
+    AccountSource::send_and_confirm_transaction(client, &tx)
+        .await
+        .unwrap();
+
+    let metadata = decode(client, &mint).await.unwrap();
+    assert_eq!(metadata.data.name.trim_end_matches('\0'), "Fixture #2");
+    assert_eq!(metadata.data.seller_fee_basis_points, 250);
+    assert!(metadata.data.creators.unwrap()[0].verified);
+
+    let payer = validator.payer.pubkey().to_string();
+    let mints = vec![mint.to_string()];
+
+    let by_update_authority =
+        get_mint_accounts(client, &None, 0, Some(payer.clone()), false, false)
+            .await
+            .unwrap();
+    assert_eq!(by_update_authority, mints);
+
+    let by_creator = get_mint_accounts(client, &Some(payer.clone()), 0, None, false, false)
+        .await
+        .unwrap();
+    assert_eq!(by_creator, mints);
+
+    let holders = snapshot_holders(
+        client,
+        SnapshotHoldersArgs {
+            creator: None,
+            position: 0,
+            update_authority: Some(payer.clone()),
+            mint_accounts_file: None,
+            v2: false,
+            allow_unverified: false,
+            output: String::new(),
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(holders.len(), 1);
+    assert_eq!(holders[0].owner_wallet, payer);
+    assert_eq!(holders[0].mint_account, mint.to_string());
+    assert_eq!(
+        holders[0].metadata_account,
+        derive_metadata_pda(&mint).to_string()
+    );
+    assert_eq!(
+        holders[0].associated_token_address,
+        get_associated_token_address(&validator.payer.pubkey(), &mint).to_string()
+    );
+
+    let records = snapshot_metadata(
+        client,
+        SnapshotMetadataArgs {
+            creator: Some(payer.clone()),
+            position: 0,
+            update_authority: None,
+            v2: false,
+            allow_unverified: false,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].mint, mint.to_string());
+    assert_eq!(records[0].name, "Fixture #2");
+    assert_eq!(records[0].seller_fee_basis_points, 250);
+    assert_eq!(records[0].creators[0].address, payer);
+    assert!(records[0].creators[0].verified);
+    assert_eq!(records[0].supply, Some(1));
+    assert_eq!(records[0].holder.as_deref(), Some(payer.as_str()));
+}
+
+#[tokio::test]
+#[ignore = "needs solana-test-validator and tests/fixtures/programs/mpl_token_metadata.so"]
+async fn test_burn() {
+    let validator = TestValidator::start().await;
+    let client = &validator.client;
+    let mint = validator.mint_fixture_nft("Fixture #1", None).await;
+
+    let args = BurnArgs {
+        client,
+        keypair: validator.payer.clone(),
+        mint_pubkey: mint,
+    };
+    let tx = burn(&args).await.unwrap();
+    AccountSource::send_and_confirm_transaction(client, &tx)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        decode(client, &mint).await,
+        Err(DecodeError::MissingAccount(_))
+    ));
+}
+
+#[tokio::test]
+#[ignore = "needs solana-test-validator and tests/fixtures/programs/mpl_token_metadata.so"]
+async fn test_collection_verify_and_migrate() {
+    let validator = TestValidator::start().await;
+    let client = &validator.client;
+    let payer = validator.payer.pubkey();
+    let collection_mint = validator.mint_fixture_nft("Collection", Some(0)).await;
+    let items = [
+        validator.mint_fixture_nft("Item #1", None).await,
+        validator.mint_fixture_nft("Item #2", None).await,
+    ];
+    let mint_list: Vec<String> = items.iter().map(|m| m.to_string()).collect();
+
+    set_and_verify_nft_collection(
+        client,
+        Some(validator.payer_path.clone()),
+        mint_list[0].clone(),
+        collection_mint.to_string(),
+        payer.to_string(),
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        count_verified_items(client, &collection_mint, &mint_list)
+            .await
+            .unwrap(),
+        1
+    );
+
+    unverify_nft_collection(
+        client,
+        Some(validator.payer_path.clone()),
+        mint_list[0].clone(),
+        collection_mint.to_string(),
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        count_verified_items(client, &collection_mint, &mint_list)
+            .await
+            .unwrap(),
+        0
+    );
+
+    let args = MigrateArgs {
+        client,
+        keypair: Keypair::from_bytes(&validator.payer.to_bytes()).unwrap(),
+        mint_address: collection_mint.to_string(),
+        candy_machine_id: None,
+        mint_list: Some(mint_list.clone()),
+        retries: 1,
+        batch_size: 2,
+    };
+    // Migration retries failed mints until they succeed, so bound the wait.
+    tokio::time::timeout(Duration::from_secs(120), migrate_collection(&args))
+        .await
+        .expect("Migration did not finish")
+        .unwrap();
+
+    assert_eq!(
+        count_verified_items(client, &collection_mint, &mint_list)
+            .await
+            .unwrap(),
+        2
+    );
+    let collection = decode(client, &collection_mint).await.unwrap();
+    assert!(matches!(
+        collection.collection_details,
+        Some(CollectionDetails::V1 { size: 2 })
+    ));
+}